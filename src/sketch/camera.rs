@@ -0,0 +1,85 @@
+use crate::sketch::*;
+
+/// Maps world space, where the points live, to screen space, where nannou draws
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Camera {
+    /// The world position shown at the centre of the window
+    pub position: Vec2,
+    pub zoom: f32,
+}
+
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 20.0;
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            position: Vec2::ZERO,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    pub(crate) fn screen_to_world(&self, screen: Vec2) -> Vec2 {
+        screen / self.zoom + self.position
+    }
+
+    pub(crate) fn world_to_screen(&self, world: Vec2) -> Vec2 {
+        (world - self.position) * self.zoom
+    }
+
+    /// A draw whose coordinates are in world space
+    pub(crate) fn transform(&self, draw: &Draw) -> Draw {
+        draw.scale(self.zoom).translate((-self.position).extend(0.0))
+    }
+
+    /// Zooms by `factor` while keeping the world position under `screen` fixed
+    pub(crate) fn zoom_around(&mut self, screen: Vec2, factor: f32) {
+        let before = self.screen_to_world(screen);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let after = self.screen_to_world(screen);
+        self.position += before - after;
+    }
+
+    /// Moves the view so the world follows a drag of `screen_delta` pixels
+    pub(crate) fn pan(&mut self, screen_delta: Vec2) {
+        self.position -= screen_delta / self.zoom;
+    }
+}
+
+/// What happens to the world when the window changes size
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum ResizeMode {
+    /// The world grows with the window and every point keeps its relative position
+    Rescale,
+    /// The world keeps its size and is fitted into the window with bars on the sides
+    Letterbox,
+}
+
+impl SettingsChoice for ResizeMode {
+    const ALL: &'static [Self] = &[ResizeMode::Rescale, ResizeMode::Letterbox];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ResizeMode::Rescale => "Rescale points",
+            ResizeMode::Letterbox => "Letterbox",
+        }
+    }
+}
+
+impl Camera {
+    /// Centres `world` in a window of `window_size` as large as it fits
+    pub(crate) fn fit(world: Rect, window_size: Vec2) -> Camera {
+        let zoom = (window_size / world.wh()).min_element();
+        Camera {
+            position: world.xy(),
+            zoom,
+        }
+    }
+}
+
+/// Maps `pos` from `from` to the same relative position in `to`
+pub(crate) fn remap(pos: Vec2, from: Rect, to: Rect) -> Vec2 {
+    to.xy() + (pos - from.xy()) * to.wh() / from.wh()
+}
@@ -0,0 +1,102 @@
+use crate::sketch::*;
+
+/// Twice the signed area, positive when the polygon winds anticlockwise
+fn signed_area2(polygon: &[Vec2]) -> f32 {
+    let len = polygon.len();
+    (0..len).map(|i| polygon[i].perp_dot(polygon[(i + 1) % len])).sum()
+}
+
+/// The same polygon wound anticlockwise, without a repeated closing point
+pub(crate) fn anticlockwise(polygon: &[Vec2]) -> Vec<Vec2> {
+    let mut polygon = polygon.to_vec();
+    if polygon.len() > 1 && polygon.first() == polygon.last() {
+        polygon.pop();
+    }
+    if signed_area2(&polygon) < 0.0 {
+        polygon.reverse();
+    }
+    polygon
+}
+
+/// Keeps the part of `polygon` at least `distance` to the left of the line through `a` and `b`
+fn clip_half_plane(polygon: &[Vec2], a: Vec2, b: Vec2, distance: f32) -> Vec<Vec2> {
+    let inward = (b - a).perp().normalize_or_zero();
+    let side = |p: Vec2| (p - a).dot(inward) - distance;
+
+    let mut clipped = vec![];
+    for (i, &current) in polygon.iter().enumerate() {
+        let next = polygon[(i + 1) % polygon.len()];
+        let (s_current, s_next) = (side(current), side(next));
+        if s_current >= 0.0 {
+            clipped.push(current);
+        }
+        if (s_current >= 0.0) != (s_next >= 0.0) {
+            let t = s_current / (s_current - s_next);
+            clipped.push(current + (next - current) * t);
+        }
+    }
+    clipped
+}
+
+/// Moves every edge of a convex anticlockwise polygon in by `distance`.
+/// Cells thinner than twice the distance disappear, so this can return nothing.
+pub(crate) fn inset_polygon(polygon: &[Vec2], distance: f32) -> Vec<Vec2> {
+    let mut inset = polygon.to_vec();
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        inset = clip_half_plane(&inset, a, b, distance);
+        if inset.len() < 3 {
+            return vec![];
+        }
+    }
+    inset
+}
+
+/// Rounds the corners of a convex anticlockwise polygon to `radius` without changing its edges:
+/// it is shrunk by the radius and then grown back out with an arc round every corner.
+/// The radius is halved until the cell is big enough to take it.
+pub(crate) fn round_polygon(polygon: &[Vec2], radius: f32) -> Vec<Vec2> {
+    let mut radius = radius;
+    let mut core = inset_polygon(polygon, radius);
+    for _ in 0..4 {
+        if !core.is_empty() {
+            break;
+        }
+        radius /= 2.0;
+        core = inset_polygon(polygon, radius);
+    }
+    if core.is_empty() {
+        return polygon.to_vec();
+    }
+
+    let len = core.len();
+    let mut rounded = vec![];
+    for i in 0..len {
+        let (previous, current, next) = (core[(i + len - 1) % len], core[i], core[(i + 1) % len]);
+        // outward normals of the edges either side of the corner
+        let start = -(current - previous).perp();
+        let end = -(next - current).perp();
+        let start_angle = start.y.atan2(start.x);
+        let sweep = (end.y.atan2(end.x) - start_angle).rem_euclid(TAU);
+
+        let segments = (sweep / (PI / 12.0)).ceil().max(1.0) as usize;
+        for s in 0..=segments {
+            let angle = start_angle + sweep * s as f32 / segments as f32;
+            rounded.push(current + Vec2::new(angle.cos(), angle.sin()) * radius);
+        }
+    }
+    rounded
+}
+
+/// Chaikin corner cutting of a closed polygon, every pass replaces each edge with its quarter points
+pub(crate) fn chaikin(polygon: &[Vec2], iterations: usize) -> Vec<Vec2> {
+    let mut smoothed = polygon.to_vec();
+    for _ in 0..iterations {
+        let len = smoothed.len();
+        smoothed = (0..len).flat_map(|i| {
+            let (a, b) = (smoothed[i], smoothed[(i + 1) % len]);
+            [a.lerp(b, 0.25), a.lerp(b, 0.75)]
+        }).collect();
+    }
+    smoothed
+}
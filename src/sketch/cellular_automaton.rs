@@ -0,0 +1,244 @@
+use std::ops::RangeInclusive;
+use crate::sketch::*;
+use crate::sketch::delaunay::delaunay_neighbours;
+use crate::sketch::renderers::voronoi::VORONOI_RENDER_MODE;
+
+/// Automaton state of each point's cell
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) struct Life {
+    pub alive: bool,
+    /// Generations spent in the current state
+    pub age: u32,
+}
+
+/// How living and dead cells are drawn in the Voronoi diagram
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum AutomatonColour {
+    /// Living cells keep the point's colour, dead ones go dark
+    State,
+    /// Living cells go through the palette as they get older
+    Age,
+}
+
+impl SettingsChoice for AutomatonColour {
+    const ALL: &'static [Self] = &[AutomatonColour::State, AutomatonColour::Age];
+
+    fn name(&self) -> &'static str {
+        match self {
+            AutomatonColour::State => "Alive or dead",
+            AutomatonColour::Age => "Age",
+        }
+    }
+}
+
+/// An outer-totalistic rule where the counts are fractions of each cell's neighbours,
+/// as Voronoi cells don't all have the same number of them.
+/// The defaults are the hexagonal B2/S34 Game of Life, widened so cells with five or seven neighbours
+/// are still born on two and survive on three, and with seven on four as well.
+#[derive(Clone, Debug)]
+pub(crate) struct AutomatonSettings {
+    pub rate: SettingsItem,
+    pub birth_min: SettingsItem,
+    pub birth_max: SettingsItem,
+    pub survive_min: SettingsItem,
+    pub survive_max: SettingsItem,
+    pub colour: AutomatonColour,
+    pub age_span: SettingsItem,
+}
+
+impl AutomatonSettings {
+    pub(crate) fn show(&mut self, ui: &mut Ui) {
+        self.rate.show(ui);
+        self.birth_min.show(ui);
+        self.birth_max.show(ui);
+        self.survive_min.show(ui);
+        self.survive_max.show(ui);
+        show_choice(ui, "Colour cells by", &mut self.colour);
+        if self.colour == AutomatonColour::Age {
+            self.age_span.show(ui);
+        }
+    }
+}
+
+impl Default for AutomatonSettings {
+    fn default() -> Self {
+        let fraction = |label: &str, value: f32| SettingsItem {
+            slider_label: label.to_string(),
+            slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                value,
+                range_min: 0.0,
+                range_max: 1.0,
+            }),
+            show_slider: true,
+            bool_label: "".to_string(),
+            bool: false,
+            show_bool: false,
+        };
+
+        AutomatonSettings {
+            rate: SettingsItem {
+                slider_label: "Generations per second".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 4.0,
+                    range_min: 0.5,
+                    range_max: 30.0,
+                }),
+                show_slider: true,
+                bool_label: "Run automaton?".to_string(),
+                bool: false,
+                show_bool: true,
+            },
+            birth_min: fraction("Birth from neighbours alive", 0.28),
+            birth_max: fraction("Birth up to neighbours alive", 0.4),
+            survive_min: fraction("Survive from neighbours alive", 0.42),
+            survive_max: fraction("Survive up to neighbours alive", 0.7),
+            colour: AutomatonColour::Age,
+            age_span: SettingsItem {
+                slider_label: "Generations across the palette".to_string(),
+                slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                    value: 20,
+                    range_min: 1,
+                    range_max: 100,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+        }
+    }
+}
+
+/// Time carried over towards the next generation
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct Automaton {
+    since_step: f32,
+}
+
+pub(crate) fn randomise_cells(points: &mut [Point]) {
+    for point in points.iter_mut() {
+        point.life = Life { alive: random_f32() < 0.3, age: 0 };
+    }
+}
+
+/// The generation after `lives`, where a cell is born or survives when the fraction of its
+/// neighbours that are alive falls in `birth` or `survive`
+fn next_generation(lives: &[Life], neighbours: &[Vec<usize>], birth: RangeInclusive<f32>, survive: RangeInclusive<f32>) -> Vec<Life> {
+    lives.iter().zip(neighbours).map(|(life, around)| {
+        let alive = around.iter().filter(|&&j| lives[j].alive).count();
+        let fraction = if around.is_empty() { 0.0 } else { alive as f32 / around.len() as f32 };
+        let alive = if life.alive { survive.contains(&fraction) } else { birth.contains(&fraction) };
+        let age = if life.alive == alive { life.age.saturating_add(1) } else { 0 };
+        Life { alive, age }
+    }).collect()
+}
+
+fn step_automaton(model: &mut Model) {
+    let settings = &model.settings.automaton;
+    let birth = settings.birth_min.value_f32()..=settings.birth_max.value_f32();
+    let survive = settings.survive_min.value_f32()..=settings.survive_max.value_f32();
+
+    let lives = model.points.iter().map(|p| p.life).collect::<Vec<Life>>();
+    let next = next_generation(&lives, &delaunay_neighbours(model), birth, survive);
+    for (point, life) in model.points.iter_mut().zip(next) {
+        point.life = life;
+    }
+}
+
+/// Steps the automaton at the chosen rate while it is switched on and the Voronoi cells are shown
+pub(crate) fn update_automaton(model: &mut Model, dt: f32) {
+    let rate = &model.settings.automaton.rate;
+    if !rate.bool || model.settings.render_mode != VORONOI_RENDER_MODE {
+        model.automaton = Automaton::default();
+        return;
+    }
+    // nothing left alive would stay that way, so it starts again
+    if model.points.iter().all(|p| !p.life.alive) {
+        randomise_cells(&mut model.points);
+    }
+
+    let interval = 1.0 / rate.value_f32();
+    model.automaton.since_step += dt;
+    // a slow frame shouldn't queue up a burst of generations
+    let steps = ((model.automaton.since_step / interval) as usize).min(4);
+    model.automaton.since_step = (model.automaton.since_step - steps as f32 * interval).min(interval);
+    for _ in 0..steps {
+        step_automaton(model);
+    }
+}
+
+/// The colour a Voronoi cell is drawn with, its point's own colour unless the automaton is running
+pub(crate) fn automaton_colour(model: &Model, point: &Point) -> LinSrgb<f32> {
+    let settings = &model.settings.automaton;
+    if !settings.rate.bool {
+        return point.colour;
+    }
+
+    let dead = lin_srgb(0.03, 0.03, 0.035);
+    match (settings.colour, point.life.alive) {
+        (_, false) => dead,
+        (AutomatonColour::State, true) => point.colour,
+        (AutomatonColour::Age, true) => {
+            let span = settings.age_span.value_u32() as f32;
+            model.palettes.active().sample_scalar((point.life.age as f32 / span).min(1.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ring of six cells around a centre, each ring cell touching the centre and its two ring neighbours
+    fn hexagon() -> Vec<Vec<usize>> {
+        let mut neighbours = vec![(1..7).collect::<Vec<usize>>()];
+        for i in 1..7 {
+            neighbours.push(vec![0, 1 + (i + 4) % 6, 1 + i % 6]);
+        }
+        neighbours
+    }
+
+    fn lives(alive: &[bool]) -> Vec<Life> {
+        alive.iter().map(|&alive| Life { alive, age: 5 }).collect()
+    }
+
+    #[test]
+    fn births_and_survivals_follow_the_live_fraction() {
+        let neighbours = hexagon();
+        // birth on exactly a third of the neighbours alive, survival on a half
+        let birth = 0.3..=0.34;
+        let survive = 0.45..=0.55;
+
+        // the centre has 3 of 6 alive and survives, the other live cells have 1 of 3 and die,
+        // and the dead ones see all 3 of theirs alive so aren't born
+        let start = lives(&[true, true, false, true, false, true, false]);
+        let next = next_generation(&start, &neighbours, birth.clone(), survive.clone());
+        let alive = next.iter().map(|l| l.alive).collect::<Vec<bool>>();
+        assert_eq!(alive, [true, false, false, false, false, false, false]);
+
+        // the centre now sees nothing alive and dies, while every ring cell sees only the centre and is born
+        let alive = next_generation(&next, &neighbours, birth, survive).iter().map(|l| l.alive).collect::<Vec<bool>>();
+        assert_eq!(alive, [false, true, true, true, true, true, true]);
+    }
+
+    #[test]
+    fn age_counts_generations_in_the_same_state() {
+        let neighbours = hexagon();
+        let start = lives(&[true, false, false, false, false, false, false]);
+        // nothing is born, everything alive survives
+        let next = next_generation(&start, &neighbours, 2.0..=2.0, 0.0..=1.0);
+        assert!(next.iter().zip(&start).all(|(a, b)| a.alive == b.alive && a.age == 6));
+
+        // everything alive dies, so the centre starts counting again
+        let next = next_generation(&next, &neighbours, 2.0..=2.0, 2.0..=2.0);
+        assert_eq!(next[0], Life { alive: false, age: 0 });
+        assert!(next[1..].iter().all(|l| !l.alive && l.age == 7));
+    }
+
+    #[test]
+    fn lone_cells_see_nothing_alive() {
+        let start = lives(&[true, false]);
+        let next = next_generation(&start, &[vec![], vec![]], 0.0..=0.0, 0.5..=1.0);
+        assert_eq!(next, [Life { alive: false, age: 0 }, Life { alive: true, age: 0 }]);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::sketch::*;
+use crate::sketch::density::DensitySampling;
 
 pub(crate) fn centroid(polygon: &Vec<Vec2>) -> Option<Vec2> {
     let len = polygon.len();
@@ -24,3 +25,168 @@ pub(crate) fn centroid(polygon: &Vec<Vec2>) -> Option<Vec2> {
         false => {Some(p)}
     }
 }
+
+/// Centroid of the polygon where each part of the area is weighted by `density`.
+/// Falls back to the unweighted centroid if the density is zero over the whole cell.
+pub(crate) fn weighted_centroid(polygon: &Vec<Vec2>, density: impl Fn(Vec2) -> f32, sampling: DensitySampling, resolution: usize) -> Option<Vec2> {
+    if polygon.len() < 3 {
+        return None;
+    }
+
+    let (mass, moment) = match sampling {
+        DensitySampling::TriangleFan => integrate_triangle_fan(polygon, &density, resolution.max(1)),
+        DensitySampling::Raster => integrate_raster(polygon, &density, resolution.max(1)),
+    };
+
+    if mass <= 0.0 {
+        return centroid(polygon);
+    }
+
+    let p = moment / mass;
+
+    match vec2_is_wrong(&p) {
+        true => {None}
+        false => {Some(p)}
+    }
+}
+
+/// Splits the polygon into a triangle fan and every triangle into `n`² smaller triangles,
+/// each sampled once at its centre. Returns the total mass and the first moment.
+fn integrate_triangle_fan(polygon: &Vec<Vec2>, density: &impl Fn(Vec2) -> f32, n: usize) -> (f32, Vec2) {
+    let step = 1.0 / n as f32;
+    let origin = polygon[0];
+
+    let mut mass = 0.0;
+    let mut moment = Vec2::ZERO;
+
+    for edge in polygon[1..].windows(2) {
+        let (u, v) = (edge[0] - origin, edge[1] - origin);
+        let area = 0.5 * (u.x * v.y - u.y * v.x).abs() * step * step;
+
+        let mut sample = |a: f32, b: f32| {
+            let pos = origin + u * a * step + v * b * step;
+            let weight = density(pos).max(0.0) * area;
+            mass += weight;
+            moment += pos * weight;
+        };
+
+        for i in 0..n {
+            for j in 0..(n - i) {
+                let (i, j) = (i as f32, j as f32);
+                sample(i + 1.0 / 3.0, j + 1.0 / 3.0);
+                if i + j + 1.0 < n as f32 {
+                    sample(i + 2.0 / 3.0, j + 2.0 / 3.0);
+                }
+            }
+        }
+    }
+
+    (mass, moment)
+}
+
+/// Samples an `n` by `n` grid over the bounding box of the polygon, keeping the samples inside it.
+/// Returns the total mass and the first moment.
+fn integrate_raster(polygon: &Vec<Vec2>, density: &impl Fn(Vec2) -> f32, n: usize) -> (f32, Vec2) {
+    let min = polygon.iter().fold(Vec2::splat(f32::MAX), |a, b| a.min(*b));
+    let max = polygon.iter().fold(Vec2::splat(f32::MIN), |a, b| a.max(*b));
+    let cell = (max - min) / n as f32;
+    let area = cell.x * cell.y;
+
+    let mut mass = 0.0;
+    let mut moment = Vec2::ZERO;
+
+    for y in 0..n {
+        for x in 0..n {
+            let pos = min + cell * Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            if polygon_contains(polygon, pos) {
+                let weight = density(pos).max(0.0) * area;
+                mass += weight;
+                moment += pos * weight;
+            }
+        }
+    }
+
+    (mass, moment)
+}
+
+/// Even-odd crossing test
+pub(crate) fn polygon_contains(polygon: &Vec<Vec2>, pos: Vec2) -> bool {
+    let len = polygon.len();
+    let mut inside = false;
+    for i in 0..len {
+        let (a, b) = (polygon[i], polygon[(i + 1) % len]);
+        if (a.y > pos.y) != (b.y > pos.y) && pos.x < (b.x - a.x) * (pos.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Unsigned area by the shoelace formula
+pub(crate) fn polygon_area(polygon: &Vec<Vec2>) -> f32 {
+    let len = polygon.len();
+    0.5 * polygon.iter().enumerate().map(|(i, a)| {
+        let b = &polygon[(i + 1) % len];
+        a.x * b.y - b.x * a.y
+    }).sum::<f32>().abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLINGS: [DensitySampling; 2] = [DensitySampling::TriangleFan, DensitySampling::Raster];
+
+    fn unit_square() -> Vec<Vec2> {
+        vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)]
+    }
+
+    fn close(a: Vec2, b: Vec2, tolerance: f32) -> bool {
+        a.distance(b) < tolerance
+    }
+
+    #[test]
+    fn contains_handles_concave_polygons() {
+        // an L with the top right quarter missing
+        let l_shape = vec![
+            Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0), Vec2::new(1.0, 2.0), Vec2::new(0.0, 2.0),
+        ];
+        assert!(polygon_contains(&l_shape, Vec2::new(0.5, 0.5)));
+        assert!(polygon_contains(&l_shape, Vec2::new(1.5, 0.5)));
+        assert!(polygon_contains(&l_shape, Vec2::new(0.5, 1.5)));
+        assert!(!polygon_contains(&l_shape, Vec2::new(1.5, 1.5)));
+        assert!(!polygon_contains(&l_shape, Vec2::new(-0.5, 0.5)));
+        assert!(!polygon_contains(&l_shape, Vec2::new(0.5, 2.5)));
+    }
+
+    #[test]
+    fn uniform_density_gives_the_plain_centroid() {
+        let triangle = vec![Vec2::new(0.0, 0.0), Vec2::new(3.0, 0.0), Vec2::new(0.0, 3.0)];
+        for polygon in [unit_square(), triangle] {
+            let expected = centroid(&polygon).unwrap();
+            for sampling in SAMPLINGS {
+                let weighted = weighted_centroid(&polygon, |_| 1.0, sampling, 64).unwrap();
+                assert!(close(weighted, expected, 0.02), "{:?} {:?} {:?}", sampling, weighted, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn density_pulls_the_centroid_towards_it() {
+        // density x over the unit square puts the centroid at x = (1/3) / (1/2)
+        for sampling in SAMPLINGS {
+            let weighted = weighted_centroid(&unit_square(), |pos| pos.x, sampling, 32).unwrap();
+            assert!(close(weighted, Vec2::new(2.0 / 3.0, 0.5), 0.01), "{:?} {:?}", sampling, weighted);
+        }
+    }
+
+    #[test]
+    fn empty_density_falls_back_to_the_plain_centroid() {
+        for sampling in SAMPLINGS {
+            let weighted = weighted_centroid(&unit_square(), |_| 0.0, sampling, 8).unwrap();
+            assert!(close(weighted, Vec2::new(0.5, 0.5), 1e-4));
+        }
+        assert_eq!(weighted_centroid(&vec![Vec2::ZERO, Vec2::ONE], |_| 1.0, DensitySampling::Raster, 8), None);
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::VecDeque;
+use crate::sketch::*;
+use crate::sketch::centroid::polygon_area;
+use crate::sketch::delaunay::delaunay_neighbours;
+
+/// What each point's colour is worked out from every frame
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum ColourBy {
+    /// The colour it was generated or painted with
+    Original,
+    Speed,
+    CellArea,
+    /// Number of Delaunay neighbours
+    Degree,
+    /// Steps through the Delaunay graph from the root point
+    BfsDepth,
+    MouseDistance,
+    Noise,
+    /// Catalyst concentration of the reaction-diffusion running over the cells
+    Concentration,
+}
+
+impl SettingsChoice for ColourBy {
+    const ALL: &'static [Self] = &[
+        ColourBy::Original,
+        ColourBy::Speed,
+        ColourBy::CellArea,
+        ColourBy::Degree,
+        ColourBy::BfsDepth,
+        ColourBy::MouseDistance,
+        ColourBy::Noise,
+        ColourBy::Concentration,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ColourBy::Original => "Original colour",
+            ColourBy::Speed => "Speed",
+            ColourBy::CellArea => "Voronoi cell area",
+            ColourBy::Degree => "Delaunay neighbours",
+            ColourBy::BfsDepth => "BFS depth from root",
+            ColourBy::MouseDistance => "Distance to mouse",
+            ColourBy::Noise => "Noise value",
+            ColourBy::Concentration => "Reaction diffusion",
+        }
+    }
+}
+
+/// The range values are mapped onto the palette with, following the data so it fills the gradient
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct ColourRange {
+    min: f32,
+    max: f32,
+    /// What was measured, the range starts again when this changes
+    by: Option<ColourBy>,
+}
+
+impl Default for ColourRange {
+    fn default() -> Self {
+        ColourRange { min: 0.0, max: 1.0, by: None }
+    }
+}
+
+impl ColourRange {
+    /// Moves towards the extremes of `values`, `smoothing` of 0 snaps straight to them
+    fn fit(&mut self, values: &[f32], by: ColourBy, smoothing: f32) {
+        let finite = values.iter().copied().filter(|x| x.is_finite());
+        let (min, max) = finite.fold((f32::MAX, f32::MIN), |(min, max), x| (min.min(x), max.max(x)));
+        if min > max {
+            return;
+        }
+
+        if self.by != Some(by) {
+            self.min = min;
+            self.max = max;
+            self.by = Some(by);
+        } else {
+            self.min = min + (self.min - min) * smoothing;
+            self.max = max + (self.max - max) * smoothing;
+        }
+    }
+
+    fn normalise(&self, value: f32) -> f32 {
+        if self.max - self.min > f32::EPSILON {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.5
+        }
+    }
+}
+
+/// The value for each point, or `None` when points keep their own colour
+fn point_values(app: &App, model: &Model) -> Option<Vec<f32>> {
+    let values = match model.settings.colour_by {
+        ColourBy::Original => return None,
+        ColourBy::Speed => {
+            // a point wrapping to the other side of the world is not fast
+            let max_step = model.world.wh().min_element() / 2.0;
+            model.points.iter().map(|p| {
+                let speed = p.pos.distance(p.last_pos);
+                if speed > max_step { 0.0 } else { speed }
+            }).collect()
+        }
+        ColourBy::CellArea => {
+            let mut areas = vec![0.0; model.points.len()];
+            for (i, (polygon, _)) in voronoi_diagram(app, model).iter().enumerate().take(areas.len()) {
+                areas[i] = polygon_area(polygon);
+            }
+            areas
+        }
+        ColourBy::Degree => delaunay_neighbours(model).iter().map(|x| x.len() as f32).collect(),
+        ColourBy::BfsDepth => bfs_depths(model),
+        ColourBy::MouseDistance => {
+            let mouse = model.mouse_world(app);
+            model.points.iter().map(|p| p.pos.distance(mouse)).collect()
+        }
+        ColourBy::Noise => model.points.iter().map(|p| model.noise.value(model.world, p.pos)).collect(),
+        ColourBy::Concentration => model.points.iter().map(|p| p.reagents.v).collect(),
+    };
+    Some(values)
+}
+
+/// Depth of every point in a breadth first search from the root, unreachable points get the deepest depth
+fn bfs_depths(model: &Model) -> Vec<f32> {
+    let root = model.path_ends.source
+        .and_then(|id| model.points.iter().position(|p| p.id == id))
+        .unwrap_or(0);
+    let neighbours = delaunay_neighbours(model);
+
+    let mut depths = vec![usize::MAX; model.points.len()];
+    let mut queue = VecDeque::new();
+    if root < depths.len() {
+        depths[root] = 0;
+        queue.push_back(root);
+    }
+    while let Some(current) = queue.pop_front() {
+        for &neighbour in neighbours[current].iter() {
+            if depths[neighbour] == usize::MAX {
+                depths[neighbour] = depths[current] + 1;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    let deepest = depths.iter().copied().filter(|&x| x != usize::MAX).max().unwrap_or(0);
+    depths.into_iter().map(|x| x.min(deepest) as f32).collect()
+}
+
+/// Sets every point's drawn colour from the chosen data, through the active palette
+pub(crate) fn recolour(app: &App, model: &mut Model) {
+    match point_values(app, model) {
+        None => {
+            model.points.iter_mut().for_each(|p| p.colour = p.base_colour);
+            model.colour_range = ColourRange::default();
+        }
+        Some(values) => {
+            model.colour_range.fit(&values, model.settings.colour_by, model.settings.colour_range_smoothing.value_f32());
+            let palette = model.palettes.active();
+            for (point, value) in model.points.iter_mut().zip(values) {
+                point.colour = palette.sample_scalar(model.colour_range.normalise(value));
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::sketch::*;
+
+/// The space colours are averaged in wherever several get blended into one
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum MixSpace {
+    /// Physically correct light mixing, but blends of strong hues go muddy
+    LinearRgb,
+    /// Perceptually even, opposite hues meet in grey
+    Oklab,
+    /// Lightness and chroma averaged, hue taken as a circular mean so it stays saturated
+    Oklch,
+}
+
+impl SettingsChoice for MixSpace {
+    const ALL: &'static [Self] = &[MixSpace::LinearRgb, MixSpace::Oklab, MixSpace::Oklch];
+
+    fn name(&self) -> &'static str {
+        match self {
+            MixSpace::LinearRgb => "Linear RGB",
+            MixSpace::Oklab => "OKLab",
+            MixSpace::Oklch => "OKLCH",
+        }
+    }
+}
+
+/// Weighted average of `colours`. Weights don't need to add up to one, negative ones count as zero,
+/// and if none are positive every colour counts equally.
+pub(crate) fn mix_colours(colours: &[(LinSrgb<f32>, f32)], space: MixSpace) -> LinSrgb<f32> {
+    if colours.is_empty() {
+        return lin_srgb(0.0, 0.0, 0.0);
+    }
+
+    let total = colours.iter().map(|(_, w)| w.max(0.0)).sum::<f32>();
+    let weights = colours.iter()
+        .map(|(_, w)| if total > 0.0 { w.max(0.0) / total } else { 1.0 / colours.len() as f32 })
+        .collect::<Vec<f32>>();
+
+    match space {
+        MixSpace::LinearRgb => {
+            let (r, g, b) = colours.iter().zip(weights).fold((0.0, 0.0, 0.0), |sum, ((c, _), w)| {
+                (sum.0 + c.red * w, sum.1 + c.green * w, sum.2 + c.blue * w)
+            });
+            lin_srgb(r, g, b)
+        }
+        MixSpace::Oklab => {
+            let (l, a, b) = colours.iter().zip(weights).fold((0.0, 0.0, 0.0), |sum, ((c, _), w)| {
+                let lab = to_oklab(*c);
+                (sum.0 + lab.l * w, sum.1 + lab.a * w, sum.2 + lab.b * w)
+            });
+            from_oklab(palette::Oklab::new(l, a, b))
+        }
+        MixSpace::Oklch => {
+            let mut l = 0.0;
+            let mut chroma = 0.0;
+            // the hue is averaged as a vector, weighted by chroma so greys don't drag it around
+            let mut hue = Vec2::ZERO;
+            for ((c, _), w) in colours.iter().zip(weights) {
+                let lch = palette::Oklch::from_color_unclamped(to_oklab(*c));
+                l += lch.l * w;
+                chroma += lch.chroma * w;
+                let angle = lch.hue.into_radians();
+                hue += Vec2::new(angle.cos(), angle.sin()) * lch.chroma * w;
+            }
+            let lch = palette::Oklch::new(l, chroma, hue.y.atan2(hue.x).to_degrees());
+            from_oklab(palette::Oklab::from_color_unclamped(lch))
+        }
+    }
+}
+
+/// Equal parts of every colour
+pub(crate) fn average_colours(colours: &[LinSrgb<f32>], space: MixSpace) -> LinSrgb<f32> {
+    let weighted = colours.iter().map(|c| (*c, 1.0)).collect::<Vec<(LinSrgb<f32>, f32)>>();
+    mix_colours(&weighted, space)
+}
+
+fn to_oklab(c: LinSrgb<f32>) -> palette::Oklab {
+    palette::Oklab::from_color_unclamped(palette::LinSrgb::new(c.red, c.green, c.blue))
+}
+
+fn from_oklab(c: palette::Oklab) -> LinSrgb<f32> {
+    let linsrgb = palette::LinSrgb::from_color_unclamped(c).clamp();
+    lin_srgb(linsrgb.red, linsrgb.green, linsrgb.blue)
+}
@@ -12,7 +12,7 @@ pub fn delaunay_triangulation(app: &App, model: &Model, corners: bool) -> Vec<(P
     let mut x = model.points.iter().map(|x| Point64::from(x.clone())).collect::<Vec<Point64>>();
 
     if corners {
-        app.window_rect().corners_iter().for_each(|pos| {
+        model.world.corners_iter().for_each(|pos| {
             let p = Point64::from(pos);
             x.push(p);
 
@@ -30,10 +30,70 @@ pub fn delaunay_triangulation(app: &App, model: &Model, corners: bool) -> Vec<(P
     }).collect::<Vec<(Point, Point, Point)>>()
 }
 
+/// Triangles of the triangulation as indices into `model.points`
+pub fn delaunay_triangles(model: &Model) -> Vec<[usize; 3]> {
+    triangles_of(&model.points)
+}
+
+pub fn triangles_of(points: &[Point]) -> Vec<[usize; 3]> {
+    let x = points.iter().map(|x| Point64::from(*x)).collect::<Vec<Point64>>();
+    let triangulation = triangulate(&x);
+    triangulation.triangles.chunks(3).map(|t| [t[0], t[1], t[2]]).collect()
+}
+
+/// Unique edges of the triangulation as indices into `model.points`, smaller index first
+pub fn delaunay_edges(model: &Model) -> Vec<(usize, usize)> {
+    edges_of(&model.points)
+}
+
+pub fn edges_of(points: &[Point]) -> Vec<(usize, usize)> {
+    let mut edges = triangles_of(points).into_iter()
+        .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect::<Vec<(usize, usize)>>();
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+/// Delaunay edges between neighbouring cells with the length of the Voronoi edge the two cells share,
+/// which runs between the circumcentres of the triangles either side. Edges on the hull are left out.
+pub fn voronoi_edge_lengths(model: &Model) -> Vec<(usize, usize, f32)> {
+    let x = model.points.iter().map(|x| Point64::from(*x)).collect::<Vec<Point64>>();
+    let triangulation = triangulate(&x);
+    let triangles = &triangulation.triangles;
+    let circumcentre = |e: usize| {
+        let t = e - e % 3;
+        x[triangles[t]].circumcenter(&x[triangles[t + 1]], &x[triangles[t + 2]])
+    };
+
+    (0..triangles.len())
+        .filter(|&e| triangulation.halfedges[e] != EMPTY && e < triangulation.halfedges[e])
+        .map(|e| {
+            let length = circumcentre(e).dist2(&circumcentre(triangulation.halfedges[e])).sqrt();
+            (triangles[e], triangles[next_halfedge(e)], length as f32)
+        })
+        .collect()
+}
+
+/// For each point, the indices of the points it shares a triangulation edge with
+pub fn delaunay_neighbours(model: &Model) -> Vec<Vec<usize>> {
+    neighbours_of(&model.points)
+}
+
+pub fn neighbours_of(points: &[Point]) -> Vec<Vec<usize>> {
+    let mut neighbours = vec![vec![]; points.len()];
+    for (a, b) in edges_of(points) {
+        neighbours[a].push(b);
+        neighbours[b].push(a);
+    }
+    neighbours
+}
+
 /// This function returns a vec of polygons with the points
 pub fn voronoi_diagram(app: &App, model: &Model) -> Vec<(Vec<Vec2>, LinSrgb<f32>)> {
-    let bounds_min = (app.window_rect().x.start as f64, app.window_rect().y.start as f64);
-    let bounds_max = (app.window_rect().x.end as f64, app.window_rect().y.end as f64);
+    let bounds_min = (model.world.x.start as f64, model.world.y.start as f64);
+    let bounds_max = (model.world.x.end as f64, model.world.y.end as f64);
 
     let points = model.points.iter()
         .map(|p| (p.pos.x as f64, p.pos.y as f64))
@@ -206,6 +266,20 @@ pub struct Triangulation {
     /// A vector of indices that reference points on the convex hull of the triangulation,
     /// counter-clockwise.
     pub hull: Vec<usize>,
+
+    /// Every change made while triangulating, in order, when asked for with `triangulate_recorded`.
+    pub events: Option<Vec<TriangulationEvent>>,
+}
+
+/// One step of building the triangulation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriangulationEvent {
+    /// The point with this index is about to be added
+    Insert(usize),
+    /// A triangle was appended, starting at half-edge `t`
+    AddTriangle { t: usize, vertices: [usize; 3] },
+    /// The edge shared by half-edges `a` and `b` was flipped, which rewrites their start points
+    Flip { a: usize, b: usize, p0: usize, p1: usize },
 }
 
 impl Triangulation {
@@ -216,6 +290,13 @@ impl Triangulation {
             triangles: Vec::with_capacity(max_triangles * 3),
             halfedges: Vec::with_capacity(max_triangles * 3),
             hull: Vec::new(),
+            events: None,
+        }
+    }
+
+    fn record(&mut self, event: TriangulationEvent) {
+        if let Some(events) = &mut self.events {
+            events.push(event);
         }
     }
 
@@ -229,6 +310,7 @@ impl Triangulation {
         c: usize,
     ) -> usize {
         let t = self.triangles.len();
+        self.record(TriangulationEvent::AddTriangle { t, vertices: [i0, i1, i2] });
 
         self.triangles.push(i0);
         self.triangles.push(i1);
@@ -285,6 +367,7 @@ impl Triangulation {
 
         let illegal = points[p0].in_circle(&points[pr], &points[pl], &points[p1]);
         if illegal {
+            self.record(TriangulationEvent::Flip { a, b, p0, p1 });
             self.triangles[a] = p1;
             self.triangles[b] = p0;
 
@@ -516,6 +599,33 @@ fn handle_collinear_points(points: &[Point64]) -> Triangulation {
 /// Returns the triangulation for the input points.
 /// For the degenerated case when all points are collinear, returns an empty triangulation where all points are in the hull.
 pub fn triangulate(points: &[Point64]) -> Triangulation {
+    triangulate_with_events(points, false)
+}
+
+/// Triangulate a set of 2D points, keeping every insertion, new triangle and flip in `events`
+/// so the construction can be replayed.
+pub fn triangulate_recorded(points: &[Point64]) -> Triangulation {
+    triangulate_with_events(points, true)
+}
+
+/// The `triangles` of the triangulation as they stood after the recorded `events`,
+/// so replaying every event of `triangulate_recorded` gives back its `triangles`.
+pub fn replay_triangles(events: &[TriangulationEvent]) -> Vec<usize> {
+    let mut triangles = Vec::new();
+    for event in events {
+        match *event {
+            TriangulationEvent::Insert(_) => {}
+            TriangulationEvent::AddTriangle { vertices, .. } => triangles.extend(vertices),
+            TriangulationEvent::Flip { a, b, p0, p1 } => {
+                triangles[a] = p1;
+                triangles[b] = p0;
+            }
+        }
+    }
+    triangles
+}
+
+fn triangulate_with_events(points: &[Point64], record: bool) -> Triangulation {
     let seed_triangle = find_seed_triangle(points);
     if seed_triangle.is_none() {
         return handle_collinear_points(points);
@@ -527,6 +637,9 @@ pub fn triangulate(points: &[Point64]) -> Triangulation {
     let center = points[i0].circumcenter(&points[i1], &points[i2]);
 
     let mut triangulation = Triangulation::new(n);
+    if record {
+        triangulation.events = Some(vec![TriangulationEvent::Insert(i0), TriangulationEvent::Insert(i1), TriangulationEvent::Insert(i2)]);
+    }
     triangulation.add_triangle(i0, i1, i2, EMPTY, EMPTY, EMPTY);
 
     // sort the points by distance from the seed triangle circumcenter
@@ -557,6 +670,7 @@ pub fn triangulate(points: &[Point64]) -> Triangulation {
         if e == EMPTY {
             continue; // likely a near-duplicate point; skip it
         }
+        triangulation.record(TriangulationEvent::Insert(i));
 
         // add the first triangle from the point
         let t = triangulation.add_triangle(e, i, hull.next[e], EMPTY, EMPTY, hull.tri[e]);
@@ -633,3 +747,50 @@ fn f64_floor(f: f64) -> f64 {
 fn f64_sqrt(f: f64) -> f64 {
     f.sqrt()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scattered(n: usize) -> Vec<Point64> {
+        let mut state: u32 = 9;
+        let mut next = move || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f64 / (1 << 24) as f64
+        };
+        (0..n).map(|_| Point64 { x: next() * 500.0, y: next() * 400.0 }).collect()
+    }
+
+    #[test]
+    fn replaying_the_events_rebuilds_the_triangulation() {
+        let points = scattered(80);
+        let triangulation = triangulate_recorded(&points);
+        let events = triangulation.events.clone().unwrap();
+
+        assert_eq!(replay_triangles(&events), triangulation.triangles);
+        assert_eq!(triangulate(&points).triangles, triangulation.triangles);
+
+        let mut inserted = events.iter().filter_map(|e| match e {
+            TriangulationEvent::Insert(i) => Some(*i),
+            _ => None,
+        }).collect::<Vec<usize>>();
+        inserted.sort_unstable();
+        assert_eq!(inserted, (0..points.len()).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn every_step_leaves_counter_clockwise_triangles() {
+        let points = scattered(40);
+        let events = triangulate_recorded(&points).events.unwrap();
+
+        for shown in 1..=events.len() {
+            let triangles = replay_triangles(&events[..shown]);
+            if let TriangulationEvent::AddTriangle { t, .. } = events[shown - 1] {
+                assert_eq!(t + 3, triangles.len());
+            }
+            for t in triangles.chunks(3) {
+                assert!(points[t[0]].orient(&points[t[1]], &points[t[2]]) < 0.0, "step {}", shown);
+            }
+        }
+    }
+}
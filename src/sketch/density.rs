@@ -0,0 +1,57 @@
+use crate::sketch::*;
+use crate::sketch::image_field::ImageField;
+use crate::sketch::noise_field::NoiseField;
+
+/// What the centroid push weights each Voronoi cell by
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum CentroidDensity {
+    Uniform,
+    Perlin,
+    Image,
+    Mouse,
+}
+
+impl SettingsChoice for CentroidDensity {
+    const ALL: &'static [Self] = &[CentroidDensity::Uniform, CentroidDensity::Perlin, CentroidDensity::Image, CentroidDensity::Mouse];
+
+    fn name(&self) -> &'static str {
+        match self {
+            CentroidDensity::Uniform => "Uniform",
+            CentroidDensity::Perlin => "Perlin noise",
+            CentroidDensity::Image => "Image darkness",
+            CentroidDensity::Mouse => "Mouse falloff",
+        }
+    }
+}
+
+/// How the density is integrated over a cell
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum DensitySampling {
+    TriangleFan,
+    Raster,
+}
+
+impl SettingsChoice for DensitySampling {
+    const ALL: &'static [Self] = &[DensitySampling::TriangleFan, DensitySampling::Raster];
+
+    fn name(&self) -> &'static str {
+        match self {
+            DensitySampling::TriangleFan => "Triangle fan",
+            DensitySampling::Raster => "Raster",
+        }
+    }
+}
+
+/// `mouse` is the cursor in world space
+pub(crate) fn density_at(density: CentroidDensity, settings: &Settings, image: &ImageField, noise: &NoiseField, world: Rect, mouse: Vec2, pos: Vec2) -> f32 {
+    match density {
+        CentroidDensity::Uniform => 1.0,
+        CentroidDensity::Perlin => noise.value(world, pos).clamp(0.0, 1.0),
+        CentroidDensity::Image => image.darkness(world, pos),
+        CentroidDensity::Mouse => {
+            let radius = settings.density_falloff.value_f32();
+            let distance = mouse.distance(pos);
+            E.powf(-(distance / radius).powi(2))
+        }
+    }
+}
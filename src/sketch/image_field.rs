@@ -0,0 +1,47 @@
+use crate::sketch::*;
+use crate::sketch::point::convert;
+use image::RgbaImage;
+
+/// A decoded image stretched over a rect so it can be sampled in window coordinates
+#[derive(Clone, Debug)]
+pub(crate) struct ImageField {
+    pixels: RgbaImage,
+}
+
+impl ImageField {
+    pub(crate) fn puppy() -> ImageField {
+        let image = image::load_from_memory(include_bytes!("../../puppy_blur.jpg")).unwrap();
+        ImageField { pixels: image.to_rgba8() }
+    }
+
+    fn pixel(&self, rect: Rect, pos: Vec2) -> image::Rgba<u8> {
+        let prop_x = ((pos.x - rect.left()) / rect.w()).clamp(0.0, 1.0);
+        let prop_y = ((rect.top() - pos.y) / rect.h()).clamp(0.0, 1.0);
+
+        let x = (prop_x * (self.pixels.width() - 1) as f32).round() as u32;
+        let y = (prop_y * (self.pixels.height() - 1) as f32).round() as u32;
+
+        *self.pixels.get_pixel(x, y)
+    }
+
+    pub(crate) fn colour(&self, rect: Rect, pos: Vec2) -> LinSrgb<f32> {
+        convert(self.pixel(rect, pos))
+    }
+
+    /// Returns 0.0 for white and 1.0 for black, using the perceptual (gamma encoded) luma
+    pub(crate) fn darkness(&self, rect: Rect, pos: Vec2) -> f32 {
+        let [r, g, b, _] = self.pixel(rect, pos).0;
+        let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+        1.0 - luma / 255.0
+    }
+
+    /// Up to `max` pixel colours spread evenly over the image
+    pub(crate) fn sample_colours(&self, max: usize) -> Vec<LinSrgb<f32>> {
+        let count = (self.pixels.width() * self.pixels.height()) as usize;
+        let step = (count / max.max(1)).max(1);
+        self.pixels.pixels()
+            .step_by(step)
+            .map(|x| convert(*x))
+            .collect()
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::{BinaryHeap, HashMap};
+use crate::sketch::*;
+use crate::sketch::centroid::polygon_contains;
+
+pub(crate) const BUBBLES_RENDER_MODE: u8 = 7;
+
+fn segment_distance(pos: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_squared() > 0.0 { ((pos - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0) } else { 0.0 };
+    pos.distance(a + ab * t)
+}
+
+/// How far `pos` is from the outline and the circles already placed, negative outside the polygon or inside a circle
+fn clearance(polygon: &Vec<Vec2>, circles: &[(Vec2, f32)], pos: Vec2) -> f32 {
+    let len = polygon.len();
+    let outline = (0..len)
+        .map(|i| segment_distance(pos, polygon[i], polygon[(i + 1) % len]))
+        .fold(f32::MAX, f32::min);
+    let outline = if polygon_contains(polygon, pos) { outline } else { -outline };
+
+    circles.iter()
+        .map(|(centre, radius)| pos.distance(*centre) - radius)
+        .fold(outline, f32::min)
+}
+
+/// A square of the search, ordered by the best clearance anything inside it could have
+struct SearchCell {
+    centre: Vec2,
+    half: f32,
+    clearance: f32,
+    potential: f32,
+}
+
+impl SearchCell {
+    fn new(polygon: &Vec<Vec2>, circles: &[(Vec2, f32)], centre: Vec2, half: f32) -> SearchCell {
+        let clearance = clearance(polygon, circles, centre);
+        // clearance changes no faster than the distance moved, and the corners are half * sqrt 2 away
+        SearchCell { centre, half, clearance, potential: clearance + half * SQRT_2 }
+    }
+}
+
+impl PartialEq for SearchCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.potential == other.potential
+    }
+}
+
+impl Eq for SearchCell {}
+
+impl PartialOrd for SearchCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SearchCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.potential.partial_cmp(&other.potential).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// The largest circle inside `polygon` that stays clear of `circles`, to within `precision`.
+/// This is the pole of inaccessibility found by subdividing squares best first (the polylabel method).
+pub(crate) fn largest_circle(polygon: &Vec<Vec2>, circles: &[(Vec2, f32)], precision: f32) -> Option<(Vec2, f32)> {
+    if polygon.len() < 3 {
+        return None;
+    }
+
+    let (min, max) = polygon.iter().fold((Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)), |(min, max), p| (min.min(*p), max.max(*p)));
+    let size = max - min;
+    let cell_size = size.min_element();
+    if !(cell_size > 0.0) {
+        return None;
+    }
+
+    let half = cell_size / 2.0;
+    let mut queue = BinaryHeap::new();
+    let mut y = min.y;
+    while y < max.y {
+        let mut x = min.x;
+        while x < max.x {
+            queue.push(SearchCell::new(polygon, circles, Vec2::new(x + half, y + half), half));
+            x += cell_size;
+        }
+        y += cell_size;
+    }
+
+    // the centroid is usually a good first guess for convex cells
+    let mut best = SearchCell::new(polygon, circles, (min + max) / 2.0, 0.0);
+    if let Some(c) = centroid(polygon) {
+        let guess = SearchCell::new(polygon, circles, c, 0.0);
+        if guess.clearance > best.clearance {
+            best = guess;
+        }
+    }
+
+    while let Some(cell) = queue.pop() {
+        if cell.clearance > best.clearance {
+            best = SearchCell::new(polygon, circles, cell.centre, 0.0);
+        }
+        if cell.potential - best.clearance <= precision {
+            continue;
+        }
+
+        let half = cell.half / 2.0;
+        for offset in [Vec2::new(-half, -half), Vec2::new(half, -half), Vec2::new(-half, half), Vec2::new(half, half)] {
+            queue.push(SearchCell::new(polygon, circles, cell.centre + offset, half));
+        }
+    }
+
+    if best.clearance > 0.0 {
+        Some((best.centre, best.clearance))
+    } else {
+        None
+    }
+}
+
+/// Greedily places up to `count` circles, each the largest that still fits, stopping once they get smaller than `min_radius`
+pub(crate) fn pack_circles(polygon: &Vec<Vec2>, count: usize, min_radius: f32, precision: f32) -> Vec<(Vec2, f32)> {
+    let mut circles = vec![];
+    while circles.len() < count {
+        match largest_circle(polygon, &circles, precision) {
+            // the first circle is always kept, however small
+            Some(circle) if circles.is_empty() || circle.1 >= min_radius => circles.push(circle),
+            _ => break,
+        }
+    }
+    circles
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Bubble {
+    pub centre: Vec2,
+    pub radius: f32,
+    pub colour: LinSrgb<f32>,
+}
+
+/// The bubbles being drawn, with the radius each one has grown to so far
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Bubbles {
+    pub bubbles: Vec<Bubble>,
+    /// Keyed by the id of the cell's point and which circle of the cell it is
+    grown: HashMap<(usize, usize), f32>,
+}
+
+/// Packs every Voronoi cell with circles and eases the drawn ones towards them
+pub(crate) fn update_bubbles(app: &App, model: &mut Model, dt: f32) {
+    if model.settings.render_mode != BUBBLES_RENDER_MODE {
+        return;
+    }
+
+    let settings = model.settings.for_mode(BUBBLES_RENDER_MODE);
+    let count = settings[0].value_u32() as usize;
+    let min_radius = settings[1].value_f32();
+    let growth = &settings[2];
+    let precision = settings[3].value_f32();
+    // how much of the way to the target is left after this frame
+    let remaining = if growth.bool { (-growth.value_f32() * dt).exp() } else { 0.0 };
+
+    let cells = voronoi_diagram(app, model);
+    let mut bubbles = vec![];
+    let mut grown = HashMap::new();
+    for (i, (polygon, colour)) in cells.iter().enumerate() {
+        let id = model.points.get(i).map_or(i, |p| p.id);
+        for (k, (centre, target)) in pack_circles(polygon, count, min_radius, precision).into_iter().enumerate() {
+            let previous = model.bubbles.grown.get(&(id, k)).copied().unwrap_or(0.0);
+            let radius = target + (previous - target) * remaining;
+            grown.insert((id, k), radius);
+            bubbles.push(Bubble { centre, radius, colour: *colour });
+        }
+    }
+
+    model.bubbles = Bubbles { bubbles, grown };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rectangle(w: f32, h: f32) -> Vec<Vec2> {
+        vec![Vec2::new(0.0, 0.0), Vec2::new(w, 0.0), Vec2::new(w, h), Vec2::new(0.0, h)]
+    }
+
+    /// Whether the circle is inside the rectangle and clear of the others, to within `precision`
+    fn fits(w: f32, h: f32, others: &[(Vec2, f32)], (centre, radius): (Vec2, f32), precision: f32) -> bool {
+        let inside = centre.x - radius > -precision && centre.y - radius > -precision
+            && centre.x + radius < w + precision && centre.y + radius < h + precision;
+        inside && others.iter().all(|(c, r)| centre.distance(*c) > r + radius - precision)
+    }
+
+    #[test]
+    fn finds_the_inscribed_circle() {
+        let (centre, radius) = largest_circle(&rectangle(10.0, 10.0), &[], 0.01).unwrap();
+        assert!(centre.distance(Vec2::new(5.0, 5.0)) < 0.05);
+        assert!((radius - 5.0).abs() < 0.01);
+
+        // a long thin cell has its widest point anywhere along the middle
+        let (centre, radius) = largest_circle(&rectangle(20.0, 6.0), &[], 0.01).unwrap();
+        assert!((radius - 3.0).abs() < 0.01);
+        assert!((centre.y - 3.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn keeps_clear_of_circles_already_placed() {
+        let placed = [(Vec2::new(5.0, 5.0), 3.0)];
+        let circle = largest_circle(&rectangle(10.0, 10.0), &placed, 0.01).unwrap();
+        assert!(circle.1 > 0.5);
+        assert!(fits(10.0, 10.0, &placed, circle, 0.01));
+    }
+
+    #[test]
+    fn packed_circles_fit_and_shrink() {
+        let circles = pack_circles(&rectangle(10.0, 10.0), 8, 0.1, 0.01);
+        assert_eq!(circles.len(), 8);
+        for (i, circle) in circles.iter().enumerate() {
+            assert!(fits(10.0, 10.0, &circles[..i], *circle, 0.01), "{} {:?}", i, circle);
+        }
+        assert!(circles.windows(2).all(|x| x[1].1 <= x[0].1 + 0.01));
+    }
+
+    #[test]
+    fn degenerate_cells_have_no_circle() {
+        assert_eq!(largest_circle(&vec![Vec2::ZERO, Vec2::ONE], &[], 0.01), None);
+        assert_eq!(largest_circle(&vec![Vec2::ZERO, Vec2::new(1.0, 0.0), Vec2::new(2.0, 0.0)], &[], 0.01), None);
+    }
+}
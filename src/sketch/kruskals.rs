@@ -1,68 +1,295 @@
+use std::collections::BinaryHeap;
 use crate::sketch::*;
 
-#[derive(Clone, Debug)]
+/// An edge between two points, by index into the points the tree was built over
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) struct Edge {
-    pub start: Point,
-    pub end: Point,
+    pub start: usize,
+    pub end: usize,
     pub weight: f32,
 }
 
-pub(crate) fn kruskals_mst(edges: Vec<(Point, Point)>) -> Vec<Edge> {
-    let mut forest = DisjointSet::new(edges.len() * 2);
-    // Assuming at most 2 unique points per edge
-    let mut mst: Vec<Edge> = Vec::new();
-    let mut sorted_edges: Vec<Edge> = edges
-        .into_iter()
-        .map(|(start, end)| Edge {
-            start,
-            end,
-            weight: start.pos.distance(end.pos),
-        })
-        .collect();
-
-    sorted_edges.sort_by(|a, b| a.weight.partial_cmp(&b.weight).unwrap());
-
-    for edge in sorted_edges {
-        if forest.find(&edge.start) != forest.find(&edge.end) {
-            forest.union(&edge.start, &edge.end);
+impl Edge {
+    /// The weight to order edges by. NaN goes last with the infinities, as `total_cmp` would put
+    /// a NaN with its sign bit set, which invalid float operations give on x86, before everything else.
+    fn sort_weight(&self) -> f32 {
+        if self.weight.is_nan() { f32::INFINITY } else { self.weight }
+    }
+}
+
+/// How the minimum spanning tree gets built, they all give the same tree up to ties
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum MstAlgorithm {
+    /// Shortest edges first, skipping any that would close a cycle
+    Kruskal,
+    /// Grow one tree outwards by its shortest edge to a new point
+    Prim,
+    /// Every tree joins its cheapest neighbour, halving the number of trees each round
+    Boruvka,
+}
+
+impl SettingsChoice for MstAlgorithm {
+    const ALL: &'static [Self] = &[MstAlgorithm::Kruskal, MstAlgorithm::Prim, MstAlgorithm::Boruvka];
+
+    fn name(&self) -> &'static str {
+        match self {
+            MstAlgorithm::Kruskal => "Kruskal's",
+            MstAlgorithm::Prim => "Prim's",
+            MstAlgorithm::Boruvka => "Borůvka's",
+        }
+    }
+}
+
+/// The minimum spanning forest of the graph, one tree per connected part
+pub(crate) fn minimum_spanning_tree(points: &[Vec2], edges: &[(usize, usize)], algorithm: MstAlgorithm) -> Vec<Edge> {
+    minimum_spanning_tree_weighted(points.len(), weighted_edges(points, edges), algorithm)
+}
+
+/// The minimum spanning forest over points `0..n` with the edges weighted however the caller likes
+pub(crate) fn minimum_spanning_tree_weighted(n: usize, edges: Vec<Edge>, algorithm: MstAlgorithm) -> Vec<Edge> {
+    match algorithm {
+        MstAlgorithm::Kruskal => kruskals_mst(n, edges),
+        MstAlgorithm::Prim => prims_mst(n, edges),
+        MstAlgorithm::Boruvka => boruvkas_mst(n, edges),
+    }
+}
+
+pub(crate) fn weighted_edges(points: &[Vec2], edges: &[(usize, usize)]) -> Vec<Edge> {
+    edges.iter()
+        .map(|&(start, end)| Edge { start, end, weight: points[start].distance(points[end]) })
+        .collect()
+}
+
+fn kruskals_mst(n: usize, edges: Vec<Edge>) -> Vec<Edge> {
+    kruskals(n, edges).into_iter().filter(|(_, accepted)| *accepted).map(|(edge, _)| edge).collect()
+}
+
+/// Every edge Kruskal's looks at, shortest first, and whether it joined two trees
+/// (accepted into the tree) or would have closed a cycle (rejected)
+pub(crate) fn kruskals_steps(points: &[Vec2], edges: &[(usize, usize)]) -> Vec<(Edge, bool)> {
+    kruskals(points.len(), weighted_edges(points, edges))
+}
+
+fn kruskals(n: usize, edges: Vec<Edge>) -> Vec<(Edge, bool)> {
+    let mut forest = UnionFind::new(n);
+    let mut sorted_edges = edges;
+
+    sorted_edges.sort_by(|a, b| a.sort_weight().total_cmp(&b.sort_weight()));
+
+    sorted_edges.into_iter()
+        .map(|edge| (edge, forest.union(edge.start, edge.end)))
+        .collect()
+}
+
+#[derive(Copy, Clone, Debug)]
+struct HeapEntry {
+    edge: Edge,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    /// Reversed, so the binary heap pops the shortest edge first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.edge.sort_weight().total_cmp(&self.edge.sort_weight())
+    }
+}
+
+fn prims_mst(n: usize, edges: Vec<Edge>) -> Vec<Edge> {
+    let mut neighbours: Vec<Vec<Edge>> = vec![vec![]; n];
+    for edge in edges {
+        neighbours[edge.start].push(edge);
+        neighbours[edge.end].push(Edge { start: edge.end, end: edge.start, weight: edge.weight });
+    }
+
+    let mut in_tree = vec![false; n];
+    let mut mst = Vec::new();
+    let mut heap = BinaryHeap::new();
+
+    // restarting from every point not yet reached covers graphs in more than one piece
+    for root in 0..n {
+        if in_tree[root] {
+            continue;
+        }
+        in_tree[root] = true;
+        heap.extend(neighbours[root].iter().map(|&edge| HeapEntry { edge }));
+
+        while let Some(HeapEntry { edge }) = heap.pop() {
+            if in_tree[edge.end] {
+                continue;
+            }
+            in_tree[edge.end] = true;
             mst.push(edge);
+            heap.extend(neighbours[edge.end].iter().filter(|e| !in_tree[e.end]).map(|&edge| HeapEntry { edge }));
         }
     }
 
     mst
 }
 
-// Disjoint set (Union-Find) implementation
+fn boruvkas_mst(n: usize, edges: Vec<Edge>) -> Vec<Edge> {
+    let mut forest = UnionFind::new(n);
+    let mut mst = Vec::new();
+
+    // equal lengths are split by position in the list, otherwise two trees could pick different
+    // edges of the same length to each other and close a cycle
+    let lighter = |a: usize, b: usize| edges[a].sort_weight().total_cmp(&edges[b].sort_weight()).then(a.cmp(&b)) == Ordering::Less;
+
+    loop {
+        let mut cheapest: Vec<Option<usize>> = vec![None; n];
+        for (i, edge) in edges.iter().enumerate() {
+            let (a, b) = (forest.find(edge.start), forest.find(edge.end));
+            if a == b {
+                continue;
+            }
+            for root in [a, b] {
+                if cheapest[root].map_or(true, |c| lighter(i, c)) {
+                    cheapest[root] = Some(i);
+                }
+            }
+        }
+
+        let mut joined = false;
+        for i in cheapest.into_iter().flatten() {
+            if forest.union(edges[i].start, edges[i].end) {
+                mst.push(edges[i]);
+                joined = true;
+            }
+        }
+        if !joined {
+            break;
+        }
+    }
+
+    mst
+}
+
+/// Disjoint sets over `0..n` with union by rank and path compression
 #[derive(Clone, Debug)]
-struct DisjointSet<T> {
-    parents: HashMap<T, T>,
+pub(crate) struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
 }
 
-impl<T: std::hash::Hash + Eq + Copy> DisjointSet<T> {
-    fn new(_size: usize) -> Self {
-        DisjointSet {
-            parents: HashMap::new(),
+impl UnionFind {
+    pub(crate) fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
         }
     }
 
-    fn find(&mut self, item: &T) -> T {
-        let parent = self.parents.get(item).cloned().unwrap_or_else(|| item.clone());
-        if parent != *item {
-            let root = self.find(&parent);
-            self.parents.insert(item.clone(), root.clone());
-            root
-        } else {
-            parent
+    pub(crate) fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // point everything on the way straight at the root
+        let mut x = x;
+        while self.parent[x] != root {
+            let next = self.parent[x];
+            self.parent[x] = root;
+            x = next;
         }
+
+        root
     }
 
+    /// Joins the sets holding `a` and `b`, false if they were already the same set
+    pub(crate) fn union(&mut self, a: usize, b: usize) -> bool {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+
+        match self.rank[root_a].cmp(&self.rank[root_b]) {
+            Ordering::Less => self.parent[root_a] = root_b,
+            Ordering::Greater => self.parent[root_b] = root_a,
+            Ordering::Equal => {
+                self.parent[root_b] = root_a;
+                self.rank[root_a] += 1;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALGORITHMS: [MstAlgorithm; 3] = [MstAlgorithm::Kruskal, MstAlgorithm::Prim, MstAlgorithm::Boruvka];
+
+    fn total_weight(edges: &[Edge]) -> f32 {
+        edges.iter().map(|e| e.weight).sum()
+    }
+
+    #[test]
+    fn algorithms_agree_on_total_weight() {
+        let points = [
+            Vec2::new(0.0, 0.0), Vec2::new(3.0, 1.0), Vec2::new(1.0, 4.0), Vec2::new(5.0, 5.0),
+            Vec2::new(-2.0, 3.0), Vec2::new(4.0, -2.0), Vec2::new(-1.0, -3.0), Vec2::new(2.5, 2.0),
+        ];
+        // every pair, so the tree isn't decided by which edges are offered
+        let edges = (0..points.len())
+            .flat_map(|a| (a + 1..points.len()).map(move |b| (a, b)))
+            .collect::<Vec<(usize, usize)>>();
+
+        let trees = ALGORITHMS.map(|algorithm| minimum_spanning_tree(&points, &edges, algorithm));
+        for tree in trees.iter() {
+            assert_eq!(tree.len(), points.len() - 1);
+            assert!((total_weight(tree) - total_weight(&trees[0])).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn disconnected_graph_gives_a_forest() {
+        // two triangles with nothing between them
+        let edges = vec![
+            Edge { start: 0, end: 1, weight: 1.0 },
+            Edge { start: 1, end: 2, weight: 2.0 },
+            Edge { start: 2, end: 0, weight: 3.0 },
+            Edge { start: 3, end: 4, weight: 1.0 },
+            Edge { start: 4, end: 5, weight: 2.0 },
+            Edge { start: 5, end: 3, weight: 3.0 },
+        ];
+
+        for algorithm in ALGORITHMS {
+            let forest = minimum_spanning_tree_weighted(6, edges.clone(), algorithm);
+            assert_eq!(forest.len(), 4);
+            assert_eq!(total_weight(&forest), 6.0);
+        }
+    }
 
-    fn union(&mut self, a: &T, b: &T) {
-        let root_a = self.find(a);
-        let root_b = self.find(b);
+    #[test]
+    fn nan_weight_does_not_panic() {
+        // both signs, -NaN is what x86 gives for 0.0 / 0.0 and it sorts first under total_cmp
+        for nan in [f32::NAN, -f32::NAN] {
+            let edges = vec![
+                Edge { start: 0, end: 1, weight: 1.0 },
+                Edge { start: 1, end: 2, weight: nan },
+                Edge { start: 2, end: 0, weight: 2.0 },
+            ];
 
-        if root_a != root_b {
-            self.parents.insert(root_a, root_b);
+            for algorithm in ALGORITHMS {
+                let tree = minimum_spanning_tree_weighted(3, edges.clone(), algorithm);
+                assert_eq!(tree.len(), 2);
+                assert!(tree.iter().all(|e| !e.weight.is_nan()));
+            }
         }
     }
 }
@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::f32::consts::*;
 use async_std::task::block_on;
 use nannou::color::*;
@@ -9,18 +9,38 @@ use nannou::wgpu::{Backends, DeviceDescriptor, Limits};
 use nannou::winit::event::VirtualKeyCode;
 use nannou_egui::{self, egui, Egui};
 use nannou_egui::egui::Ui;
-use noise::{NoiseFn, OpenSimplex};
 use palette;
 use palette::Clamp;
 use palette::convert::FromColorUnclamped;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::*;
 
-use crate::sketch::centroid::centroid;
+use crate::sketch::camera::*;
+use crate::sketch::cell_style::*;
+use crate::sketch::cellular_automaton::*;
+use crate::sketch::centroid::{centroid, weighted_centroid};
+use crate::sketch::colour_by::*;
+use crate::sketch::colour_mix::*;
 use crate::sketch::delaunay::{voronoi_diagram, Point64};
-use crate::sketch::kruskals::kruskals_mst;
+use crate::sketch::density::*;
+use crate::sketch::image_field::ImageField;
+use crate::sketch::inscribed::*;
+use crate::sketch::noise_field::*;
+use crate::sketch::paths::*;
+use crate::sketch::palettes::*;
+use crate::sketch::playback::*;
+use crate::sketch::kruskals::{minimum_spanning_tree, MstAlgorithm};
 use crate::sketch::point::*;
+use crate::sketch::proximity::*;
+use crate::sketch::reaction_diffusion::*;
 use crate::sketch::settings::*;
+use crate::sketch::spring_mesh::*;
+use crate::sketch::stable_tree::*;
+use crate::sketch::stipple::*;
+use crate::sketch::streamlines::*;
+use crate::sketch::tool::*;
+use crate::sketch::touch::*;
+use crate::sketch::tsp::*;
 use crate::sketch::utils::*;
 
 
@@ -32,6 +52,27 @@ mod nearest;
 mod settings;
 mod renderers;
 mod utils;
+mod image_field;
+mod stipple;
+mod density;
+mod tool;
+mod camera;
+mod touch;
+mod noise_field;
+mod streamlines;
+mod palettes;
+mod colour_by;
+mod colour_mix;
+mod cell_style;
+mod inscribed;
+mod proximity;
+mod tsp;
+mod paths;
+mod playback;
+mod stable_tree;
+mod spring_mesh;
+mod reaction_diffusion;
+mod cellular_automaton;
 
 const POINTS_SQUARE_WIDTH_PX: usize = 800;
 const POINTS_SQUARE_WIDTH_POINTS: usize = 25;
@@ -89,51 +130,182 @@ struct Model {
     // _window: WindowId,
     bg: Srgb<u8>,
     points: Vec<Point>,
+    /// Recent positions of each point, by id
+    trails: HashMap<usize, Trail>,
     settings: Settings,
     gui: Egui,
+    image: ImageField,
+    brush: Brush,
+    camera: Camera,
+    touches: Touches,
+    /// The area the points live and wrap in, independent of the window
+    world: Rect,
+    /// Window size the world was last laid out for
+    window_size: Vec2,
+    noise: NoiseField,
+    palettes: Palettes,
+    colour_range: ColourRange,
+    path_ends: PathEnds,
+    path_animation: PathAnimation,
+    bubbles: Bubbles,
+    tour: Tour,
+    streamlines: Streamlines,
+    playback: Playback,
+    stable_tree: StableTree,
+    spring_mesh: SpringMesh,
+    automaton: Automaton,
 }
 
 impl Model {
     fn new(app: &App) -> Model {
         let egui = Egui::from_window(&app.main_window());
+        let world = app.window_rect();
+        let settings = Settings::default();
+        let palettes = Palettes::default();
 
         Model {
             bg: Srgb::new(20, 20, 20),
-            points: Point::new_points(&app),
-            settings: Settings::default(),
+            points: Point::new_points(world, palettes.active()),
+            trails: HashMap::new(),
+            noise: NoiseField::new(&settings.noise),
+            settings,
             gui: egui,
+            image: ImageField::puppy(),
+            brush: Brush::default(),
+            camera: Camera::default(),
+            touches: Touches::default(),
+            world,
+            window_size: world.wh(),
+            palettes,
+            colour_range: ColourRange::default(),
+            path_ends: PathEnds::default(),
+            path_animation: PathAnimation::default(),
+            bubbles: Bubbles::default(),
+            tour: Tour::default(),
+            streamlines: Streamlines::default(),
+            playback: Playback::default(),
+            stable_tree: StableTree::default(),
+            spring_mesh: SpringMesh::default(),
+            automaton: Automaton::default(),
         }
     }
+
+    fn resized(&mut self, size: Vec2) {
+        if size.x <= 0.0 || size.y <= 0.0 {
+            // minimised
+            return;
+        }
+
+        match self.settings.resize_mode {
+            ResizeMode::Rescale => {
+                let old = self.world;
+                let new = Rect::from_xy_wh(old.xy(), old.wh() * size / self.window_size);
+                for point in self.points.iter_mut() {
+                    point.pos = remap(point.pos, old, new);
+                    point.last_pos = remap(point.last_pos, old, new);
+                    point.starting_location = remap(point.starting_location, old, new);
+                }
+                self.trails.values_mut().for_each(|trail| trail.clear());
+                self.world = new;
+            }
+            ResizeMode::Letterbox => {
+                self.camera = Camera::fit(self.world, size);
+            }
+        }
+
+        self.window_size = size;
+        self.world_changed();
+    }
+
+    /// Drops anything that was worked out for the old world bounds
+    fn world_changed(&mut self) {
+        self.brush.last_paint = None;
+        // rest lengths were measured in the old world
+        self.stable_tree = StableTree::default();
+        self.spring_mesh = SpringMesh::default();
+    }
+
+    /// The part of the world that is on screen, clipped to the world bounds
+    fn visible_world(&self, app: &App) -> Rect {
+        let window = app.window_rect();
+        let corners = [window.bottom_left(), window.top_right()].map(|x| self.camera.screen_to_world(x));
+        let visible = Rect::from_corners(corners[0], corners[1]);
+        visible.overlap(self.world).unwrap_or(self.world)
+    }
+
+    fn mouse_world(&self, app: &App) -> Vec2 {
+        self.camera.screen_to_world(app.mouse.position())
+    }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
     frame.clear(model.bg);
     let draw = app.draw();
-    let render_mode_name = match model.settings.render_mode {
-        0 => { renderers::perlin::render_perlin(app, model, &draw); "Perlin Vector Field" }
-        1 => { renderers::voronoi::render_voronoi(app, model, &draw); "Voronoi Diagram" }
-        2 => { renderers::voronoi::render_delaunay(app, model, &draw); "Delaunay Triangulation" }
-        3 => { renderers::graph::render_mst(app, model, &draw); "Minimum Spanning Team" }
-        4 => { renderers::graph::render_bfs(app, model, &draw); "Breadth First Search" }
-        5 => { renderers::points::render_speed_sizing(app, model, &draw); "Speed Sizing" }
-        6 => { renderers::points::render_point_vectors(app, model, &draw); "Movement Vectors" }
-        7 => { renderers::voronoi::render_bubbles(app, model, &draw); "Bubbles" }
-        9 => { renderers::points::render_point_vectors_coloured(app, model, &draw); "Perlin Vector Field with Arrows" }
+    let world = model.camera.transform(&draw);
+    match model.settings.render_mode {
+        0 => { renderers::perlin::render_perlin(app, model, &world) }
+        1 => { renderers::voronoi::render_voronoi(app, model, &world) }
+        2 => { renderers::voronoi::render_delaunay(app, model, &world) }
+        3 => { renderers::graph::render_mst(app, model, &world) }
+        4 => { renderers::graph::render_bfs(app, model, &world) }
+        5 => { renderers::points::render_speed_sizing(app, model, &world) }
+        6 => { renderers::points::render_point_vectors(app, model, &world) }
+        7 => { renderers::voronoi::render_bubbles(app, model, &world) }
+        8 => { renderers::stipple::render_stipple(app, model, &world) }
+        9 => { renderers::points::render_point_vectors_coloured(app, model, &world) }
+        10 => { renderers::flow::render_trails(app, model, &world) }
+        11 => { renderers::flow::render_streamlines(app, model, &world) }
+        12 => { renderers::graph::render_proximity_graph(app, model, &world) }
+        13 => { renderers::tour::render_tour(app, model, &world) }
+        14 => { renderers::graph::render_dfs(app, model, &world) }
+        15 => { renderers::graph::render_shortest_path(app, model, &world) }
+        16 => { renderers::playback::render_playback(app, model, &world) }
+        17 => { renderers::graph::render_spring_mesh(app, model, &world) }
         _ => {
             frame.clear(RED);
             return;
         }
     };
 
+    draw_letterbox(app, &draw, model);
     draw_progress_bar(app, &draw, model);
-    draw_title(app, &draw, render_mode_name);
+    draw_tool(app, &world, model);
+    draw_title(app, &draw, render_mode_name(model.settings.render_mode));
 
     draw.to_frame(app, &frame).unwrap();
     model.gui.draw_to_frame(&frame).unwrap();
 }
 
+const RENDER_MODES: [(u8, &str); 18] = [
+    (1, "Voronoi Diagram"),
+    (2, "Delaunay Triangulation"),
+    (3, "Minimum Spanning Team"),
+    (4, "Breadth First Search"),
+    (5, "Speed Sizing"),
+    (6, "Movement Vectors"),
+    (7, "Bubbles"),
+    (9, "Perlin Vector Field with Arrows"),
+    (0, "Perlin Vector Field"),
+    (8, "Stippling"),
+    (10, "Trails"),
+    (11, "Streamlines"),
+    (12, "Proximity Graph"),
+    (13, "Travelling Salesman Tour"),
+    (14, "Depth First Search"),
+    (15, "Shortest Path"),
+    (16, "Algorithm Playback"),
+    (17, "Spring Mesh"),
+];
+
+fn render_mode_name(mode: u8) -> &'static str {
+    RENDER_MODES.iter().find(|(m, _)| *m == mode).map_or("", |(_, name)| *name)
+}
+
 fn update(app: &App, model: &mut Model, update: Update) {
-    if model.settings.simulation_speed.bool {
+    model.noise.sync(&model.settings.noise, update.since_last.as_secs_f32(), model.settings.simulation_speed.bool);
+
+    // a recording only matches the points it was made with, so they hold still while it plays
+    if model.settings.simulation_speed.bool && model.settings.render_mode != PLAYBACK_RENDER_MODE {
         // sets last point for velocity calculation
         model.points.iter_mut().for_each(|x| {
             x.last_pos = x.pos;
@@ -145,8 +317,21 @@ fn update(app: &App, model: &mut Model, update: Update) {
         // then push the points toward the centroid
         if model.settings.centroid_push.bool {
             let voronoi = voronoi_diagram(app, model);
+            let stippling = model.settings.render_mode == STIPPLE_RENDER_MODE && model.settings.for_mode(STIPPLE_RENDER_MODE)[1].bool;
+            let density = model.settings.centroid_density;
+            let sampling = model.settings.centroid_sampling;
+            let resolution = model.settings.centroid_resolution.value_u32() as usize;
+            let rect = model.world;
+            let mouse = model.mouse_world(app);
             for (i, (points, _)) in voronoi.iter().enumerate() {
-                match centroid(&points) {
+                let cell_centroid = if stippling {
+                    weighted_centroid(&points, |pos| stipple_darkness(&model.settings, &model.image, rect, pos), sampling, resolution)
+                } else if density != CentroidDensity::Uniform {
+                    weighted_centroid(&points, |pos| density_at(density, &model.settings, &model.image, &model.noise, rect, mouse, pos), sampling, resolution)
+                } else {
+                    centroid(&points)
+                };
+                match cell_centroid {
                     None => {}
                     Some(centroid) => {
                         let point = model.points[i].pos;
@@ -175,7 +360,7 @@ fn update(app: &App, model: &mut Model, update: Update) {
             for point in model.points.iter_mut() {
                 let mut points = vec![];
 
-                let width_height = app.window_rect().wh();
+                let width_height = model.world.wh();
                 let width = Vec2::new(width_height.x, 0.0);
                 let height = Vec2::new(0.0, width_height.y);
 
@@ -203,14 +388,14 @@ fn update(app: &App, model: &mut Model, update: Update) {
         }
         // println!("Errors after timer pull: {}", model.count_non_finite_points());
 
-        // the following checks to see if it is outside the view window
-        for point in model.points.iter_mut() {
-            let window_rect = app.window_rect();
+        // the following checks to see if it is outside the world
+        for point in model.points.iter_mut().filter(|p| !p.pinned) {
+            let world_rect = model.world;
 
-            if !window_rect.contains(point.pos) {
-                let (x_start, x_end) = (window_rect.x.start, window_rect.x.end);
-                let (y_start, y_end) = (window_rect.y.start, window_rect.y.end);
-                let (width, height) = (window_rect.w(), window_rect.h());
+            if !world_rect.contains(point.pos) {
+                let (x_start, x_end) = (world_rect.x.start, world_rect.x.end);
+                let (y_start, y_end) = (world_rect.y.start, world_rect.y.end);
+                let (width, height) = (world_rect.w(), world_rect.h());
 
                 let (x, y) = (point.pos.x, point.pos.y);
 
@@ -231,38 +416,66 @@ fn update(app: &App, model: &mut Model, update: Update) {
 
         if model.settings.perlin_push.bool {
             for point in model.points.iter_mut() {
-                let value = tileable_perlin(&model.settings, &app, point.pos);
-                let angle = value * TAU;
-
-                let push = Vec2::new(angle.cos(), angle.sin()) * 1.0 / 10.0;
+                let push = model.noise.flow(model.settings.flow_field, model.world, point.pos) * 1.0 / 10.0;
                 point.moving_vec += push * model.settings.perlin_push.value_f32() * model.settings.simulation_speed.value_f32();
             }
         }
         // println!("Errors after perlin push: {}", model.count_non_finite_points());
 
-        if model.settings.mouse_push.bool {
+        if model.settings.mouse_push.bool && app.window_rect().contains(app.mouse.position()) {
+            let mouse = model.mouse_world(app);
+            // force touch on a touchpad presses harder, a plain mouse reports no pressure
+            let pressure = 1.0 + model.touches.touchpad_pressure;
             for point in model.points.iter_mut() {
-                let mouse = Vec2::new(app.mouse.x, app.mouse.y);
-                if app.window_rect().contains(mouse) {
-                    let vec = point.pos - mouse;
-                    let dir = vec.normalize();
-                    let distance = vec.length();
+                let new_vec = radial_push(point.pos, mouse);
+                point.moving_vec += new_vec * model.settings.simulation_speed.value_f32() * model.settings.mouse_push.value_f32() * pressure;
+            }
+        }
 
-                    // let new_length = 1.0 / distance.powf(0.7);
-                    let new_length = E.powf(-1.0 / 100.0 * distance);
+        // frames of 1/60th of a second since the last update, what every point moves by its moving_vec
+        let dt = update.since_last.as_secs_f32() * 60.0;
 
-                    let new_vec = dir * new_length;
+        apply_tree_springs(model, dt);
+        apply_spring_mesh(model, dt);
 
-                    point.moving_vec += new_vec * model.settings.simulation_speed.value_f32() * model.settings.mouse_push.value_f32();
-                }
+        let touches = model.touches.push_sources(&model.settings, &model.camera);
+        for (touch, pressure) in touches {
+            for point in model.points.iter_mut() {
+                let new_vec = radial_push(point.pos, touch);
+                point.moving_vec += new_vec * model.settings.simulation_speed.value_f32() * model.settings.touch_push.value_f32() * pressure;
             }
         }
 
-        let dt = update.since_last.as_secs_f32() * 60.0;
-        model.points.iter_mut().for_each(|p| p.pos += p.moving_vec * dt);
+        let trails = &mut model.trails;
+        model.points.iter_mut().for_each(|p| {
+            if p.pinned {
+                p.moving_vec = Vec2::ZERO;
+            } else {
+                p.pos += p.moving_vec * dt;
+            }
+            trails.entry(p.id).or_insert_with(Trail::new).push(p.pos);
+        });
+        // erased points leave their trails behind
+        if model.trails.len() > model.points.len() {
+            let ids = model.points.iter().map(|p| p.id).collect::<HashSet<usize>>();
+            model.trails.retain(|id, _| ids.contains(id));
+        }
 
         // println!("Errors after mouse push: {}", model.count_non_finite_points());
     }
+    // the playback draws its own copy of the points, so they are left as they were recorded
+    if model.settings.render_mode != PLAYBACK_RENDER_MODE {
+        apply_tool(app, model);
+    }
+    update_reaction_diffusion(model);
+    update_automaton(model, update.since_last.as_secs_f32());
+    recolour(app, model);
+    update_bubbles(app, model, update.since_last.as_secs_f32());
+    update_tour(model);
+    update_streamlines(model);
+    update_path_animation(model, update.since_last.as_secs_f32());
+    update_playback(app, model, update.since_last.as_secs_f32());
+    update_stable_tree(model, update.since_last.as_secs_f32());
     gui(app, model, update);
 }
 
@@ -270,37 +483,178 @@ fn gui(app: &App, model: &mut Model, update: Update) {
     let egui = &mut model.gui;
     egui.set_elapsed_time(update.since_start);
     let ctx = egui.begin_frame();
+    let mut world_changed = false;
+    let mut reseed = false;
 
 
     egui::Window::new("Settings").show(&ctx, |ui| {
+        model.settings.world_scale.show(ui);
+        // resetting is when the world gets resized, so the new points fill it
+        let world = Rect::from_wh(app.window_rect().wh() * model.settings.world_scale.value_f32());
+
         let clicked = ui.button("Reset to spiral").clicked();
         if clicked {
-            model.points = Point::new_points_circle(app);
+            model.world = world;
+            world_changed = true;
+            model.points = Point::new_points_circle(world, model.palettes.active());
         }
 
         let clicked = ui.button("Reset to grid").clicked();
         if clicked {
-            model.points = Point::new_points_square(app);
+            model.world = world;
+            world_changed = true;
+            model.points = Point::new_points_square(world, model.palettes.active());
         }
 
         let clicked = ui.button("Reset to nautilus").clicked();
         if clicked {
-            model.points = Point::new_points_multi_colour_spiral(app);
+            model.world = world;
+            world_changed = true;
+            model.points = Point::new_points_multi_colour_spiral(world, model.palettes.active());
         }
 
         let clicked = ui.button("Reset to puppy").clicked();
         if clicked {
-            model.points = Point::new_points_from_image(app);
+            model.world = world;
+            world_changed = true;
+            model.points = Point::new_points_from_image(world);
+        }
+
+        let clicked = ui.button("Reset to stipple").clicked();
+        if clicked {
+            let count = model.settings.for_mode(STIPPLE_RENDER_MODE)[2].value_u32();
+            model.world = world;
+            world_changed = true;
+            model.points = Point::new_points_stipple(world, &model.image, count);
+        }
+
+        let clicked = ui.button("Reset view").clicked();
+        if clicked {
+            model.camera = match model.settings.resize_mode {
+                ResizeMode::Rescale => Camera::default(),
+                ResizeMode::Letterbox => Camera::fit(model.world, app.window_rect().wh()),
+            };
+        }
+        show_choice(ui, "On window resize", &mut model.settings.resize_mode);
+
+        if model.settings.render_mode == STIPPLE_RENDER_MODE {
+            let clicked = ui.button("Export stipple SVG").clicked();
+            if clicked {
+                save_stipple_svg(&model.settings, &model.image, &model.points, model.world);
+            }
         }
 
         model.settings.simulation_speed.show(ui);
         model.settings.show_points.show(ui);
-        model.settings.perlin_seed.show(ui);
+        ui.collapsing("Palette", |ui| {
+            model.palettes.show(ui);
+            model.settings.palette_colours.show(ui);
+            let clicked = ui.button("Extract palette from image").clicked();
+            if clicked {
+                let k = model.settings.palette_colours.value_u32() as usize;
+                model.palettes.add(ColourPalette::from_image(&model.image, k));
+            }
+            ui.label("Drop a .gpl, .ase or .json palette on the window to load it");
+        });
+        show_choice(ui, "Colour points by", &mut model.settings.colour_by);
+        if model.settings.colour_by != ColourBy::Original {
+            model.settings.colour_range_smoothing.show(ui);
+        }
+        if model.settings.colour_by == ColourBy::Concentration {
+            ui.collapsing("Reaction diffusion", |ui| model.settings.reaction.show(ui));
+            let clicked = ui.button("Seed reaction").clicked();
+            if clicked {
+                reseed = true;
+            }
+        }
+        if model.settings.colour_by == ColourBy::BfsDepth {
+            ui.label("Pick the root with the path tool, or press R over a point");
+        }
+        show_choice(ui, "Mix colours in", &mut model.settings.mix_space);
+        ui.collapsing("Noise field", |ui| model.settings.noise.show(ui));
         model.settings.perlin_push.show(ui);
+        show_choice(ui, "Flow field", &mut model.settings.flow_field);
         model.settings.mouse_push.show(ui);
+        model.settings.touch_push.show(ui);
+        if model.settings.touch_push.bool {
+            let _ = ui.checkbox(&mut model.settings.touch_gestures, "Two finger pan and zoom?");
+        }
         model.settings.centroid_push.show(ui);
+        show_choice(ui, "Centroid density", &mut model.settings.centroid_density);
+        if model.settings.centroid_density != CentroidDensity::Uniform || model.settings.render_mode == STIPPLE_RENDER_MODE {
+            show_choice(ui, "Density sampling", &mut model.settings.centroid_sampling);
+            model.settings.centroid_resolution.show(ui);
+        }
+        if model.settings.centroid_density == CentroidDensity::Mouse {
+            model.settings.density_falloff.show(ui);
+        }
         model.settings.timer_pull.show(ui);
 
+        show_choice(ui, "Tool", &mut model.settings.tool);
+        if model.settings.tool == Tool::Paint {
+            model.settings.brush_radius.show(ui);
+            model.settings.paint_spacing.show(ui);
+            show_choice(ui, "Paint colour", &mut model.settings.paint_source);
+            if model.settings.paint_source == PaintColour::Picked {
+                ui.color_edit_button_rgb(&mut model.settings.paint_colour);
+            }
+        }
+        if model.settings.tool == Tool::Drag {
+            let _ = ui.checkbox(&mut model.settings.pin_dragged, "Pin dragged points?");
+            let clicked = ui.button("Unpin all").clicked();
+            if clicked {
+                model.points.iter_mut().for_each(|p| p.pinned = false);
+            }
+        }
+
+        egui::ComboBox::from_label("Render mode")
+            .selected_text(render_mode_name(model.settings.render_mode))
+            .show_ui(ui, |ui| {
+                for (mode, name) in RENDER_MODES {
+                    ui.selectable_value(&mut model.settings.render_mode, mode, name);
+                }
+            });
+
+        if model.settings.render_mode == MST_RENDER_MODE {
+            show_choice(ui, "Spanning tree", &mut model.settings.mst_algorithm);
+        }
+        if model.settings.render_mode == PROXIMITY_RENDER_MODE {
+            show_choice(ui, "Graph", &mut model.settings.proximity_graph);
+        }
+        if model.settings.render_mode == SHORTEST_PATH_RENDER_MODE {
+            show_choice(ui, "Search", &mut model.settings.path_search);
+            ui.label("Left click a source and right click a target with the path tool");
+        }
+        if model.settings.render_mode == TOUR_RENDER_MODE {
+            show_choice(ui, "Starting tour", &mut model.settings.tour_construction);
+            let clicked = ui.button("Rebuild tour").clicked();
+            if clicked {
+                model.tour = Tour::default();
+            }
+            let clicked = ui.button("Export tour SVG").clicked();
+            if clicked {
+                save_svg("tour.svg", &tour_svg(&model.settings, &model.tour, &model.points, model.world));
+            }
+        }
+
+        if model.settings.render_mode == renderers::voronoi::VORONOI_RENDER_MODE {
+            ui.collapsing("Cellular automaton", |ui| model.settings.automaton.show(ui));
+            let clicked = ui.button("Randomise cells").clicked();
+            if clicked {
+                randomise_cells(&mut model.points);
+            }
+        }
+        if model.settings.render_mode == SPRING_MESH_RENDER_MODE {
+            let clicked = ui.button("Rest springs here").clicked();
+            if clicked {
+                model.spring_mesh = SpringMesh::default();
+            }
+        }
+        if model.settings.render_mode == PLAYBACK_RENDER_MODE {
+            show_choice(ui, "Algorithm", &mut model.settings.playback_algorithm);
+            model.playback.show_controls(ui);
+        }
+
         match &mut model.settings.settings_per_render_mode[model.settings.render_mode as usize] {
             None => {}
             Some(x) => {
@@ -309,10 +663,19 @@ fn gui(app: &App, model: &mut Model, update: Update) {
             }
         }
     });
+    // the frame has to end before the rest of the model can be borrowed
+    drop(ctx);
+
+    if world_changed {
+        model.world_changed();
+    }
+    if reseed {
+        seed_reaction(model);
+    }
 }
 
 // Handle events related to the window and update the model if necessary
-fn event(_app: &App, model: &mut Model, event: WindowEvent) {
+fn event(app: &App, model: &mut Model, event: WindowEvent) {
     match event {
         KeyPressed(x) => {
             match x {
@@ -325,24 +688,39 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
                 VirtualKeyCode::Key7 => { model.settings.render_mode = 7 }
                 VirtualKeyCode::Key8 => { model.settings.render_mode = 9 }
                 VirtualKeyCode::Key9 => { model.settings.render_mode = 0 }
-                // VirtualKeyCode::Key0 => { model.settings.render_mode = 0 }
+                VirtualKeyCode::Key0 => { model.settings.render_mode = 8 }
+                VirtualKeyCode::R => {
+                    let mouse = model.mouse_world(app);
+                    model.path_ends.source = nearest_point(&model.points, mouse).map(|i| model.points[i].id);
+                }
                 _ => {}
             }
         }
         KeyReleased(_) => {}
         ReceivedCharacter(_) => {}
-        MouseMoved(_) => {}
-        MousePressed(_) => {}
-        MouseReleased(_) => {}
+        MouseMoved(pos) => { tool_moved(model, pos) }
+        MousePressed(button) => { tool_pressed(app, model, button) }
+        MouseReleased(button) => { tool_released(model, button) }
         MouseEntered => {}
         MouseExited => {}
-        MouseWheel(_, _) => {}
-        Resized(_) => {}
+        MouseWheel(delta, _) => {
+            if model.settings.tool == Tool::Paint {
+                tool_scrolled(model, delta)
+            } else if !model.gui.ctx().is_pointer_over_area() {
+                model.camera.zoom_around(app.mouse.position(), 1.1_f32.powf(scroll_amount(delta)));
+            }
+        }
+        Resized(size) => { model.resized(size) }
         HoveredFile(_) => {}
-        DroppedFile(_) => {}
+        DroppedFile(path) => {
+            match load_palette(&path) {
+                Ok(palette) => model.palettes.add(palette),
+                Err(e) => log_message(&format!("Could not load palette {}: {}", path.display(), e)),
+            }
+        }
         HoveredFileCancelled => {}
-        Touch(_) => {}
-        TouchPressure(_) => {}
+        Touch(touch) => { touch_event(model, touch) }
+        TouchPressure(pressure) => { model.touches.touchpad_pressure = pressure.pressure }
         Focused => {}
         Unfocused => {}
         Closed => {}
@@ -353,6 +731,35 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
 fn raw_window_event(_app: &App, model: &mut Model, event: &nannou::winit::event::WindowEvent) {
     // Let egui handle things like keyboard and mouse input.
     model.gui.handle_raw_event(event);
+
+    if let nannou::winit::event::WindowEvent::Touch(touch) = event {
+        raw_touch_event(model, touch);
+    }
+}
+
+/// Covers the parts of the window outside the world
+fn draw_letterbox(app: &App, draw: &Draw, model: &Model) {
+    if model.settings.resize_mode != ResizeMode::Letterbox {
+        return;
+    }
+    let win = app.window_rect();
+    let world = Rect::from_corners(
+        model.camera.world_to_screen(model.world.bottom_left()),
+        model.camera.world_to_screen(model.world.top_right()),
+    );
+
+    let bars = [
+        Rect::from_corners(win.bottom_left(), pt2(world.left(), win.top())),
+        Rect::from_corners(pt2(world.right(), win.bottom()), win.top_right()),
+        Rect::from_corners(pt2(world.left(), win.bottom()), world.bottom_right()),
+        Rect::from_corners(world.top_left(), pt2(world.right(), win.top())),
+    ];
+    for bar in bars.iter().filter(|bar| bar.w() > 0.0 && bar.h() > 0.0) {
+        draw.rect()
+            .xy(bar.xy())
+            .wh(bar.wh())
+            .color(BLACK);
+    }
 }
 
 fn draw_progress_bar(app: &App, draw: &Draw, model: &Model) {
@@ -2,7 +2,38 @@ use std::collections::{HashMap, VecDeque};
 use crate::sketch::*;
 
 
-pub(crate) fn bfs(edges: Vec<(Point, Point)>) -> (Vec<(Point, Point)>, Vec<(Point, usize)>) {
+/// The point with id `root`, or the first point in the edge list if there isn't one
+fn start_point(edges: &[(Point, Point)], root: Option<usize>) -> Option<Point> {
+    let chosen = root.and_then(|id| edges.iter().flat_map(|(a, b)| [a, b]).find(|p| p.id == id));
+    chosen.or(edges.first().map(|(a, _)| a)).cloned()
+}
+
+/// One step of a breadth first search
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum BfsStep {
+    /// The point was taken off the front of the queue and its neighbours are being looked at
+    Visit(Point),
+    /// `to` was seen for the first time from `from` and joined the back of the queue
+    Discover { from: Point, to: Point, depth: usize },
+}
+
+pub(crate) fn bfs(edges: Vec<(Point, Point)>, root: Option<usize>) -> (Vec<(Point, Point)>, Vec<(Point, usize)>) {
+    let start = start_point(&edges, root);
+    let mut tree_edges = Vec::new();
+    let mut distances = start.map(|p| (p, 0)).into_iter().collect::<Vec<_>>();
+
+    for step in bfs_steps(edges, root) {
+        if let BfsStep::Discover { from, to, depth } = step {
+            tree_edges.push((from, to));
+            distances.push((to, depth));
+        }
+    }
+
+    (tree_edges, distances)
+}
+
+/// The order a breadth first search from `root` visits and discovers points in
+pub(crate) fn bfs_steps(edges: Vec<(Point, Point)>, root: Option<usize>) -> Vec<BfsStep> {
     // Convert edge list to adjacency list
     let mut graph = HashMap::new();
     for (a, b) in &edges {
@@ -11,31 +42,72 @@ pub(crate) fn bfs(edges: Vec<(Point, Point)>) -> (Vec<(Point, Point)>, Vec<(Poin
     }
 
     let mut queue = VecDeque::new();
-    let mut visited = HashMap::new();
-    let mut tree_edges = Vec::new();
-    let mut distances = Vec::new();
+    let mut depth_of = HashMap::new();
+    let mut steps = Vec::new();
 
-    // Assume the first point in the edge list is the starting node
-    if let Some((start, _)) = edges.first() {
+    if let Some(start) = start_point(&edges, root) {
         queue.push_back(start.clone());
-        visited.insert(start.clone(), true);
-        distances.push((start.clone(), 0));
+        depth_of.insert(start.clone(), 0);
     }
 
     while let Some(current) = queue.pop_front() {
-        let current_distance = distances.iter().find(|(p, _)| p == &current).unwrap().1;
+        let current_distance = depth_of[&current];
+        steps.push(BfsStep::Visit(current));
 
         if let Some(neighbors) = graph.get(&current) {
             for neighbor in neighbors {
-                if !visited.contains_key(neighbor) {
+                if !depth_of.contains_key(neighbor) {
                     queue.push_back(neighbor.clone());
-                    visited.insert(neighbor.clone(), true);
-                    tree_edges.push((current.clone(), neighbor.clone()));
-                    distances.push((neighbor.clone(), current_distance + 1));
+                    depth_of.insert(neighbor.clone(), current_distance + 1);
+                    steps.push(BfsStep::Discover { from: current.clone(), to: neighbor.clone(), depth: current_distance + 1 });
                 }
             }
         }
     }
 
-    (tree_edges, distances)
+    steps
+}
+
+/// Depth first search tree from `root`, with how deep in the tree each point ended up
+pub(crate) fn dfs(edges: Vec<(Point, Point)>, root: Option<usize>) -> (Vec<(Point, Point)>, Vec<(Point, usize)>) {
+    let mut graph = HashMap::new();
+    for (a, b) in &edges {
+        graph.entry(a.clone()).or_insert_with(Vec::new).push(b.clone());
+        graph.entry(b.clone()).or_insert_with(Vec::new).push(a.clone());
+    }
+
+    let mut stack = Vec::new();
+    let mut depth_of = HashMap::new();
+    let mut tree_edges = Vec::new();
+    let mut depths = Vec::new();
+
+    if let Some(start) = start_point(&edges, root) {
+        stack.push((None, start));
+    }
+
+    // a point is only visited when it comes off the stack, so the tree follows the deepest route
+    while let Some((parent, current)) = stack.pop() {
+        if depth_of.contains_key(&current) {
+            continue;
+        }
+        let depth = match parent {
+            None => 0,
+            Some(parent) => {
+                tree_edges.push((parent, current));
+                depth_of[&parent] + 1
+            }
+        };
+        depth_of.insert(current, depth);
+        depths.push((current, depth));
+
+        if let Some(neighbors) = graph.get(&current) {
+            for neighbor in neighbors.iter().rev() {
+                if !depth_of.contains_key(neighbor) {
+                    stack.push((Some(current), *neighbor));
+                }
+            }
+        }
+    }
+
+    (tree_edges, depths)
 }
@@ -0,0 +1,352 @@
+use noise::core::worley::ReturnType;
+use noise::{NoiseFn, OpenSimplex, Perlin, Value, Worley};
+use crate::sketch::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum NoiseType {
+    Perlin,
+    OpenSimplex,
+    Worley,
+    Value,
+}
+
+impl SettingsChoice for NoiseType {
+    const ALL: &'static [Self] = &[NoiseType::Perlin, NoiseType::OpenSimplex, NoiseType::Worley, NoiseType::Value];
+
+    fn name(&self) -> &'static str {
+        match self {
+            NoiseType::Perlin => "Perlin",
+            NoiseType::OpenSimplex => "OpenSimplex",
+            NoiseType::Worley => "Worley",
+            NoiseType::Value => "Value",
+        }
+    }
+}
+
+/// How the noise is turned into a push direction
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum FlowField {
+    /// The noise value is used as an angle, which leaves sinks where points clump
+    Angle,
+    /// The noise is a stream function and the push is its curl, which is divergence free
+    Curl,
+}
+
+impl SettingsChoice for FlowField {
+    const ALL: &'static [Self] = &[FlowField::Angle, FlowField::Curl];
+
+    fn name(&self) -> &'static str {
+        match self {
+            FlowField::Angle => "Noise angle",
+            FlowField::Curl => "Curl noise",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct NoiseSettings {
+    pub noise_type: NoiseType,
+    pub seed: SettingsItem,
+    pub scale: SettingsItem,
+    pub octaves: SettingsItem,
+    pub lacunarity: SettingsItem,
+    pub persistence: SettingsItem,
+    pub warp: SettingsItem,
+    pub time: SettingsItem,
+}
+
+impl NoiseSettings {
+    pub(crate) fn show(&mut self, ui: &mut Ui) {
+        show_choice(ui, "Noise type", &mut self.noise_type);
+        self.seed.show(ui);
+        self.scale.show(ui);
+        self.octaves.show(ui);
+        if self.octaves.value_u32() > 1 {
+            self.lacunarity.show(ui);
+            self.persistence.show(ui);
+        }
+        self.warp.show(ui);
+        self.time.show(ui);
+    }
+}
+
+impl Default for NoiseSettings {
+    fn default() -> Self {
+        NoiseSettings {
+            noise_type: NoiseType::OpenSimplex,
+            seed: SettingsItem {
+                slider_label: "Noise seed".to_string(),
+                slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                    value: random_range(0,65535),
+                    range_min: 0,
+                    range_max: 65535,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            scale: SettingsItem {
+                slider_label: "Radial Distance Scaling Factor".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 0.5,
+                    range_min: 0.0,
+                    range_max: 2.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            octaves: SettingsItem {
+                slider_label: "Octaves".to_string(),
+                slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                    value: 1,
+                    range_min: 1,
+                    range_max: 8,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            lacunarity: SettingsItem {
+                slider_label: "Lacunarity".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 2.0,
+                    range_min: 1.0,
+                    range_max: 4.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            persistence: SettingsItem {
+                slider_label: "Persistence".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 0.5,
+                    range_min: 0.0,
+                    range_max: 1.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            warp: SettingsItem {
+                slider_label: "Domain warp strength".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 0.5,
+                    range_min: 0.0,
+                    range_max: 2.0,
+                }),
+                show_slider: true,
+                bool_label: "Domain warp?".to_string(),
+                bool: false,
+                show_bool: true,
+            },
+            time: SettingsItem {
+                slider_label: "Noise evolution speed".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 0.05,
+                    range_min: 0.0,
+                    range_max: 1.0,
+                }),
+                show_slider: true,
+                bool_label: "Evolve over time?".to_string(),
+                bool: false,
+                show_bool: true,
+            },
+        }
+    }
+}
+
+/// Direction the noise drifts through its 4D domain as time passes.
+/// Moving the whole torus keeps the field tileable.
+const TIME_DIRECTION: [f64; 4] = [1.0, 0.7, -0.4, 0.9];
+/// Offsets of the two samples that make up the warp vector, far enough apart to be uncorrelated
+const WARP_OFFSETS: [[f64; 4]; 2] = [[17.3, -4.1, 9.7, 2.2], [-8.6, 13.9, -1.5, 21.4]];
+
+/// The numbers out of `NoiseSettings` that sampling needs, so they can be copied every frame without the labels
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct NoiseParameters {
+    scale: f32,
+    octaves: u32,
+    lacunarity: f32,
+    persistence: f32,
+    /// Strength of the domain warp, if it is on
+    warp: Option<f32>,
+}
+
+impl NoiseParameters {
+    fn new(settings: &NoiseSettings) -> NoiseParameters {
+        NoiseParameters {
+            scale: settings.scale.value_f32(),
+            octaves: settings.octaves.value_u32(),
+            lacunarity: settings.lacunarity.value_f32(),
+            persistence: settings.persistence.value_f32(),
+            warp: if settings.warp.bool { Some(settings.warp.value_f32()) } else { None },
+        }
+    }
+}
+
+/// Everything the values of a `NoiseField` depend on, to tell when something worked out from it is out of date
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct NoiseState {
+    noise_type: NoiseType,
+    seed: u32,
+    parameters: NoiseParameters,
+    time: f64,
+}
+
+/// A cached noise generator together with the parameters it was sampled with this frame
+pub(crate) struct NoiseField {
+    noise_type: NoiseType,
+    seed: u32,
+    generator: Box<dyn NoiseFn<f64, 4>>,
+    parameters: NoiseParameters,
+    time: f64,
+}
+
+impl NoiseField {
+    pub(crate) fn new(settings: &NoiseSettings) -> NoiseField {
+        let noise_type = settings.noise_type;
+        let seed = settings.seed.value_u32();
+        NoiseField {
+            noise_type,
+            seed,
+            generator: generator(noise_type, seed),
+            parameters: NoiseParameters::new(settings),
+            time: 0.0,
+        }
+    }
+
+    /// Picks up changed settings, only rebuilding the generator when the type or seed changed,
+    /// and advances time while the simulation is `running`
+    pub(crate) fn sync(&mut self, settings: &NoiseSettings, dt: f32, running: bool) {
+        let seed = settings.seed.value_u32();
+        if settings.noise_type != self.noise_type || seed != self.seed {
+            self.noise_type = settings.noise_type;
+            self.seed = seed;
+            self.generator = generator(self.noise_type, seed);
+        }
+        self.parameters = NoiseParameters::new(settings);
+
+        if running && settings.time.bool {
+            self.time += (dt * settings.time.value_f32()) as f64;
+        }
+    }
+
+    pub(crate) fn state(&self) -> NoiseState {
+        NoiseState {
+            noise_type: self.noise_type,
+            seed: self.seed,
+            parameters: self.parameters,
+            time: self.time,
+        }
+    }
+
+    /// Noise that wraps around the edges of `world`, so it is continuous for points that get wrapped.
+    /// Roughly in 0..1, but can overshoot.
+    pub(crate) fn value(&self, world: Rect, pos: Vec2) -> f32 {
+        let mut coords = self.torus(world, pos);
+
+        if let Some(strength) = self.parameters.warp {
+            let offset = |o: [f64; 4]| [coords[0] + o[0], coords[1] + o[1], coords[2] + o[2], coords[3] + o[3]];
+            let warp = Vec2::new(self.fbm(offset(WARP_OFFSETS[0])), self.fbm(offset(WARP_OFFSETS[1])));
+            let warped = pos + warp * world.wh() * 0.25 * strength;
+            coords = self.torus(world, warped);
+        }
+
+        self.fbm(coords) + 0.5
+    }
+
+    /// Unit-ish push direction at `pos`
+    pub(crate) fn flow(&self, kind: FlowField, world: Rect, pos: Vec2) -> Vec2 {
+        match kind {
+            FlowField::Angle => {
+                let angle = self.value(world, pos) * TAU;
+                Vec2::new(angle.cos(), angle.sin())
+            }
+            FlowField::Curl => self.curl(world, pos),
+        }
+    }
+
+    /// Curl of the noise treated as a stream function, `(dn/dy, -dn/dx)`, by central differences in world units.
+    /// Both components are scaled by the same factor, the mean world side over the torus circumference,
+    /// so it stays divergence-free in a world of any aspect ratio and its length is around 1.
+    fn curl(&self, world: Rect, pos: Vec2) -> Vec2 {
+        let epsilon = 0.001;
+        let dx = Vec2::new(epsilon * world.w(), 0.0);
+        let dy = Vec2::new(0.0, epsilon * world.h());
+
+        let dn_dx = (self.value(world, pos + dx) - self.value(world, pos - dx)) / (2.0 * epsilon * world.w());
+        let dn_dy = (self.value(world, pos + dy) - self.value(world, pos - dy)) / (2.0 * epsilon * world.h());
+
+        let circumference = TAU * self.parameters.scale.max(0.05);
+        let side = (world.w() * world.h()).sqrt();
+        Vec2::new(dn_dy, -dn_dx) * side / circumference
+    }
+
+    /// Maps the world onto a torus in 4D so both axes wrap, shifted along `TIME_DIRECTION`
+    fn torus(&self, world: Rect, pos: Vec2) -> [f64; 4] {
+        let scale = self.parameters.scale;
+
+        let x_prop = (pos.x - world.left()) / world.w();
+        let y_prop = (pos.y - world.bottom()) / world.h();
+
+        let angle_x = x_prop * TAU;
+        let angle_y = y_prop * TAU;
+
+        let a = cos(angle_x) * scale;
+        let b = sin(angle_x) * scale;
+        let c = cos(angle_y) * scale;
+        let d = sin(angle_y) * scale;
+
+        let t = self.time;
+        [
+            a as f64 + t * TIME_DIRECTION[0],
+            b as f64 + t * TIME_DIRECTION[1],
+            c as f64 + t * TIME_DIRECTION[2],
+            d as f64 + t * TIME_DIRECTION[3],
+        ]
+    }
+
+    /// Sums octaves of the generator, normalised so the amplitudes add up to 1
+    fn fbm(&self, coords: [f64; 4]) -> f32 {
+        let octaves = self.parameters.octaves;
+        let lacunarity = self.parameters.lacunarity as f64;
+        let persistence = self.parameters.persistence as f64;
+
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        let mut total_amplitude = 0.0;
+
+        for octave in 0..octaves {
+            // shift each octave so they don't all share a lattice point at the origin
+            let shift = octave as f64 * 31.7;
+            let point = coords.map(|x| x * frequency + shift);
+            total += self.generator.get(point) * amplitude;
+            total_amplitude += amplitude;
+            frequency *= lacunarity;
+            amplitude *= persistence;
+        }
+
+        if total_amplitude > 0.0 {
+            (total / total_amplitude) as f32
+        } else {
+            0.0
+        }
+    }
+}
+
+fn generator(noise_type: NoiseType, seed: u32) -> Box<dyn NoiseFn<f64, 4>> {
+    match noise_type {
+        NoiseType::Perlin => Box::new(Perlin::new(seed)),
+        NoiseType::OpenSimplex => Box::new(OpenSimplex::new(seed)),
+        NoiseType::Worley => Box::new(Worley::new(seed).set_return_type(ReturnType::Distance)),
+        NoiseType::Value => Box::new(Value::new(seed)),
+    }
+}
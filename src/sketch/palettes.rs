@@ -0,0 +1,582 @@
+use std::path::Path;
+use crate::sketch::*;
+use crate::sketch::image_field::ImageField;
+
+/// The space colours are blended in between two stops
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum GradientSpace {
+    /// Straight lines through OKLab, which can pass through grey between opposite hues
+    Oklab,
+    /// Lightness, chroma and hue blended separately, taking the short way round the hue circle
+    Oklch,
+}
+
+impl SettingsChoice for GradientSpace {
+    const ALL: &'static [Self] = &[GradientSpace::Oklab, GradientSpace::Oklch];
+
+    fn name(&self) -> &'static str {
+        match self {
+            GradientSpace::Oklab => "OKLab",
+            GradientSpace::Oklch => "OKLCH",
+        }
+    }
+}
+
+/// A named gradient, sampled from 0.0 to 1.0
+#[derive(Clone, Debug)]
+pub(crate) struct ColourPalette {
+    pub name: String,
+    /// Positions in 0..1 with their colours, sorted by position
+    stops: Vec<(f32, palette::Oklab)>,
+    pub space: GradientSpace,
+    /// The last stop blends back into the first, for things like angles
+    cyclic: bool,
+}
+
+impl ColourPalette {
+    /// Spaces the colours out evenly. A cyclic palette leaves room after the last one to blend back to the first.
+    pub(crate) fn new(name: &str, colours: Vec<palette::Oklab>, space: GradientSpace, cyclic: bool) -> ColourPalette {
+        let divisions = if cyclic { colours.len() } else { colours.len().saturating_sub(1) }.max(1);
+        let stops = colours.into_iter()
+            .enumerate()
+            .map(|(i, colour)| (i as f32 / divisions as f32, colour))
+            .collect();
+
+        ColourPalette { name: name.to_string(), stops, space, cyclic }
+    }
+
+    fn from_hex(name: &str, colours: &[&str], space: GradientSpace) -> ColourPalette {
+        let colours = colours.iter().filter_map(|x| parse_hex(x)).map(srgb_to_oklab).collect();
+        ColourPalette::new(name, colours, space, false)
+    }
+
+    /// The sweep the sketch has always used, OKHSV hue at fixed saturation and value
+    fn rainbow() -> ColourPalette {
+        let n = 12;
+        let colours = (0..n).map(|i| {
+            let okhsv = palette::Okhsv::new(i as f32 / n as f32 * 360.0, 0.8, 1.0);
+            palette::Oklab::from_color_unclamped(okhsv)
+        }).collect();
+        ColourPalette::new("Rainbow", colours, GradientSpace::Oklch, true)
+    }
+
+    pub(crate) fn sample(&self, x: f32) -> LinSrgb<f32> {
+        oklab_to_lin_srgb(self.sample_oklab(x))
+    }
+
+    /// For data rather than angles: a cyclic palette stops at its last stop, so both ends of a range stay distinct
+    pub(crate) fn sample_scalar(&self, x: f32) -> LinSrgb<f32> {
+        match (self.cyclic, self.stops.last()) {
+            (true, Some((last, _))) => self.sample(x.clamp(0.0, 1.0) * last),
+            _ => self.sample(x),
+        }
+    }
+
+    /// Samples the palette then fades it towards white, 0.0 leaves it alone and 1.0 is white
+    pub(crate) fn sample_tinted(&self, x: f32, tint: f32) -> LinSrgb<f32> {
+        let white = palette::Oklab::new(1.0, 0.0, 0.0);
+        oklab_to_lin_srgb(self.blend(self.sample_oklab(x), white, tint.clamp(0.0, 1.0)))
+    }
+
+    fn sample_oklab(&self, x: f32) -> palette::Oklab {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return palette::Oklab::new(0.0, 0.0, 0.0),
+        };
+        let x = if self.cyclic { x.rem_euclid(1.0) } else { x.clamp(0.0, 1.0) };
+
+        let ((pos_a, a), (pos_b, b)) = match self.stops.iter().position(|(pos, _)| *pos > x) {
+            Some(0) if self.cyclic => ((last.0 - 1.0, last.1), first),
+            Some(0) => return first.1,
+            None if self.cyclic => (last, (first.0 + 1.0, first.1)),
+            None => return last.1,
+            Some(i) => (self.stops[i - 1], self.stops[i]),
+        };
+
+        let t = if pos_b > pos_a { (x - pos_a) / (pos_b - pos_a) } else { 0.0 };
+        self.blend(a, b, t)
+    }
+
+    fn blend(&self, a: palette::Oklab, b: palette::Oklab, t: f32) -> palette::Oklab {
+        match self.space {
+            GradientSpace::Oklab => palette::Oklab::new(
+                lerp(a.l, b.l, t),
+                lerp(a.a, b.a, t),
+                lerp(a.b, b.b, t),
+            ),
+            GradientSpace::Oklch => {
+                let a = palette::Oklch::from_color_unclamped(a);
+                let b = palette::Oklch::from_color_unclamped(b);
+                // greys have no real hue, so they take the other end's
+                let mut hue_a = a.hue.into_degrees();
+                let mut hue_b = b.hue.into_degrees();
+                if a.chroma < 1e-4 { hue_a = hue_b }
+                if b.chroma < 1e-4 { hue_b = hue_a }
+                let hue_difference = (hue_b - hue_a + 540.0).rem_euclid(360.0) - 180.0;
+
+                let lch = palette::Oklch::new(
+                    lerp(a.l, b.l, t),
+                    lerp(a.chroma, b.chroma, t),
+                    hue_a + hue_difference * t,
+                );
+                palette::Oklab::from_color_unclamped(lch)
+            }
+        }
+    }
+
+    /// Clusters the image's colours with k-means in OKLab and orders the centres from dark to light
+    pub(crate) fn from_image(image: &ImageField, k: usize) -> ColourPalette {
+        let samples = image.sample_colours(4096)
+            .into_iter()
+            .map(|c| palette::Oklab::from_color_unclamped(palette::LinSrgb::new(c.red, c.green, c.blue)))
+            .collect::<Vec<palette::Oklab>>();
+
+        let mut centres = kmeans(&samples, k, 20);
+        centres.sort_by(|a, b| a.l.partial_cmp(&b.l).unwrap_or(Ordering::Equal));
+
+        ColourPalette::new(&format!("Image ({} colours)", centres.len()), centres, GradientSpace::Oklab, false)
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn oklab_distance_squared(a: palette::Oklab, b: palette::Oklab) -> f32 {
+    (a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)
+}
+
+/// k-means++ seeding followed by Lloyd iterations, stopping early once nothing moves between clusters
+fn kmeans(samples: &[palette::Oklab], k: usize, iterations: usize) -> Vec<palette::Oklab> {
+    if samples.is_empty() || k == 0 {
+        return vec![];
+    }
+
+    let nearest = |centres: &[palette::Oklab], sample: palette::Oklab| {
+        centres.iter()
+            .enumerate()
+            .map(|(i, c)| (i, oklab_distance_squared(*c, sample)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+            .unwrap()
+    };
+
+    let mut centres = vec![samples[random_range(0, samples.len())]];
+    while centres.len() < k.min(samples.len()) {
+        let weights = samples.iter().map(|s| nearest(&centres, *s).1).collect::<Vec<f32>>();
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 {
+            // fewer distinct colours than clusters
+            break;
+        }
+        let mut target = random_f32() * total;
+        let chosen = weights.iter().position(|w| {
+            target -= w;
+            target <= 0.0
+        }).unwrap_or(samples.len() - 1);
+        centres.push(samples[chosen]);
+    }
+
+    let mut assignments = vec![usize::MAX; samples.len()];
+    for _ in 0..iterations {
+        let mut changed = false;
+        for (sample, assignment) in samples.iter().zip(assignments.iter_mut()) {
+            let (cluster, _) = nearest(&centres, *sample);
+            changed |= cluster != *assignment;
+            *assignment = cluster;
+        }
+        if !changed {
+            break;
+        }
+
+        let mut sums = vec![(0.0, 0.0, 0.0, 0_usize); centres.len()];
+        for (sample, &cluster) in samples.iter().zip(assignments.iter()) {
+            let sum = &mut sums[cluster];
+            sum.0 += sample.l;
+            sum.1 += sample.a;
+            sum.2 += sample.b;
+            sum.3 += 1;
+        }
+        for (centre, (l, a, b, count)) in centres.iter_mut().zip(sums) {
+            // an empty cluster keeps its old centre
+            if count > 0 {
+                *centre = palette::Oklab::new(l / count as f32, a / count as f32, b / count as f32);
+            }
+        }
+    }
+
+    centres
+}
+
+fn srgb_to_oklab(colour: palette::Srgb<f32>) -> palette::Oklab {
+    palette::Oklab::from_color_unclamped(colour.into_linear())
+}
+
+fn oklab_to_lin_srgb(colour: palette::Oklab) -> LinSrgb<f32> {
+    let linsrgb = palette::LinSrgb::from_color_unclamped(colour).clamp();
+    lin_srgb(linsrgb.red, linsrgb.green, linsrgb.blue)
+}
+
+/// Parses `#rrggbb` or `#rgb`, with or without the hash
+fn parse_hex(text: &str) -> Option<palette::Srgb<f32>> {
+    let hex = text.trim().trim_start_matches('#');
+    let digits = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect::<String>(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let value = u32::from_str_radix(&digits, 16).ok()?;
+    let [_, r, g, b] = value.to_be_bytes();
+    Some(palette::Srgb::new(r, g, b).into_format())
+}
+
+/// GIMP palette: a `GIMP Palette` header, optional `Name:` and `Columns:` lines, then `r g b name` rows
+fn parse_gpl(text: &str) -> Result<(Option<String>, Vec<palette::Srgb<f32>>), String> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        return Err("missing the GIMP Palette header".to_string());
+    }
+
+    let mut name = None;
+    let mut colours = vec![];
+    for line in lines.map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+            continue;
+        }
+        if let Some(x) = line.strip_prefix("Name:") {
+            name = Some(x.trim().to_string());
+            continue;
+        }
+        let channels = line.split_whitespace()
+            .take(3)
+            .filter_map(|x| x.parse::<u8>().ok())
+            .collect::<Vec<u8>>();
+        if let [r, g, b] = channels[..] {
+            colours.push(palette::Srgb::new(r, g, b).into_format());
+        }
+    }
+
+    Ok((name, colours))
+}
+
+/// Adobe Swatch Exchange: big endian blocks, of which only colour entries in RGB, CMYK and grey are read
+fn parse_ase(bytes: &[u8]) -> Result<Vec<palette::Srgb<f32>>, String> {
+    let u16_at = |i: usize| bytes.get(i..i + 2).map(|x| u16::from_be_bytes([x[0], x[1]]));
+    let u32_at = |i: usize| bytes.get(i..i + 4).map(|x| u32::from_be_bytes([x[0], x[1], x[2], x[3]]));
+    let f32_at = |i: usize| u32_at(i).map(f32::from_bits);
+    let truncated = || "the file ends in the middle of a block".to_string();
+
+    if bytes.get(0..4) != Some(&b"ASEF"[..]) {
+        return Err("missing the ASEF signature".to_string());
+    }
+
+    let block_count = u32_at(8).ok_or_else(truncated)?;
+    let mut i = 12;
+    let mut colours = vec![];
+
+    for _ in 0..block_count {
+        let block_type = u16_at(i).ok_or_else(truncated)?;
+        let length = u32_at(i + 2).ok_or_else(truncated)? as usize;
+        let start = i + 6;
+        i = start + length;
+
+        // group start and end blocks carry no colour
+        if block_type != 0x0001 {
+            continue;
+        }
+
+        // the name is UTF-16 with a length in code units
+        let name_length = u16_at(start).ok_or_else(truncated)? as usize;
+        let model_at = start + 2 + name_length * 2;
+        let values_at = model_at + 4;
+        let value = |n: usize| f32_at(values_at + n * 4).ok_or_else(truncated);
+
+        let colour = match bytes.get(model_at..values_at) {
+            Some(b"RGB ") => palette::Srgb::new(value(0)?, value(1)?, value(2)?),
+            Some(b"Gray") => palette::Srgb::new(value(0)?, value(0)?, value(0)?),
+            Some(b"CMYK") => {
+                let (c, m, y, k) = (value(0)?, value(1)?, value(2)?, value(3)?);
+                palette::Srgb::new((1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - y) * (1.0 - k))
+            }
+            // LAB swatches need a colour managed conversion, so are left out
+            Some(_) => continue,
+            None => return Err(truncated()),
+        };
+        colours.push(colour);
+    }
+
+    Ok(colours)
+}
+
+/// JSON palettes are read loosely: every string that is a hex colour is a stop, in order,
+/// and the value of a `"name"` key on the outermost object is the name. Covers both `["#fff", ...]`
+/// and `{"name": "...", "colours": ["#fff", ...]}` without a JSON dependency.
+fn parse_json(text: &str) -> Result<(Option<String>, Vec<palette::Srgb<f32>>), String> {
+    let mut name = None;
+    let mut colours = vec![];
+    // how many objects and arrays deep we are, and whether the string just read was a key at the top level
+    let mut depth = 0;
+    let mut name_key = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let string = parse_json_string(&mut chars)?;
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                let is_key = chars.peek() == Some(&':');
+
+                let was_name_key = name_key;
+                name_key = is_key && depth == 1 && string == "name";
+                if was_name_key && !is_key {
+                    name = Some(string);
+                } else if !is_key && string.starts_with('#') {
+                    colours.extend(parse_hex(&string));
+                }
+                continue;
+            }
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            _ => {}
+        }
+        // a name that isn't a string is left alone
+        if c != ':' && !c.is_whitespace() {
+            name_key = false;
+        }
+    }
+
+    Ok((name, colours))
+}
+
+/// The rest of a JSON string after its opening quote, with the escapes decoded
+fn parse_json_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    let mut string = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some('b') => string.push('\u{8}'),
+                Some('f') => string.push('\u{c}'),
+                Some('n') => string.push('\n'),
+                Some('r') => string.push('\r'),
+                Some('t') => string.push('\t'),
+                Some('u') => {
+                    let mut code = json_code_unit(chars)?;
+                    // characters outside the basic plane come as a surrogate pair
+                    if (0xd800..0xdc00).contains(&code) {
+                        if chars.next() != Some('\\') || chars.next() != Some('u') {
+                            return Err("unpaired surrogate in string".to_string());
+                        }
+                        let low = json_code_unit(chars)?;
+                        if !(0xdc00..0xe000).contains(&low) {
+                            return Err("unpaired surrogate in string".to_string());
+                        }
+                        code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                    }
+                    string.push(char::from_u32(code).ok_or("unpaired surrogate in string")?);
+                }
+                Some(c @ ('"' | '\\' | '/')) => string.push(c),
+                Some(c) => return Err(format!("unknown escape \\{} in string", c)),
+                None => return Err("unterminated string".to_string()),
+            },
+            Some(c) => string.push(c),
+            None => return Err("unterminated string".to_string()),
+        }
+    }
+}
+
+/// The four hex digits of a `\uXXXX` escape
+fn json_code_unit(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<u32, String> {
+    let digits = chars.take(4).collect::<String>();
+    if digits.chars().count() != 4 {
+        return Err("unterminated string".to_string());
+    }
+    u32::from_str_radix(&digits, 16).map_err(|_| format!("bad escape \\u{} in string", digits))
+}
+
+/// Reads a `.gpl`, `.ase` or `.json` palette, naming it after the file if it doesn't name itself
+pub(crate) fn load_palette(path: &Path) -> Result<ColourPalette, String> {
+    let extension = path.extension()
+        .and_then(|x| x.to_str())
+        .map(|x| x.to_lowercase())
+        .unwrap_or_default();
+    let file_name = path.file_stem()
+        .and_then(|x| x.to_str())
+        .unwrap_or("Imported")
+        .to_string();
+
+    let read_text = || std::fs::read_to_string(path).map_err(|e| e.to_string());
+    let (name, colours) = match extension.as_str() {
+        "gpl" => parse_gpl(&read_text()?)?,
+        "json" => parse_json(&read_text()?)?,
+        "ase" => (None, parse_ase(&std::fs::read(path).map_err(|e| e.to_string())?)?),
+        _ => return Err(format!("unknown palette format .{}", extension)),
+    };
+
+    if colours.is_empty() {
+        return Err("no colours found".to_string());
+    }
+
+    let colours = colours.into_iter().map(srgb_to_oklab).collect();
+    Ok(ColourPalette::new(&name.unwrap_or(file_name), colours, GradientSpace::Oklab, false))
+}
+
+/// Every palette that can be picked, and which one everything is coloured through
+#[derive(Clone, Debug)]
+pub(crate) struct Palettes {
+    pub all: Vec<ColourPalette>,
+    pub active: usize,
+}
+
+impl Default for Palettes {
+    fn default() -> Self {
+        Palettes {
+            all: vec![
+                ColourPalette::rainbow(),
+                ColourPalette::from_hex("Viridis", &["#440154", "#482878", "#3e4989", "#31688e", "#26828e", "#1f9e89", "#35b779", "#6ece58", "#b5de2b", "#fde725"], GradientSpace::Oklab),
+                ColourPalette::from_hex("Magma", &["#000004", "#1c1044", "#4f127b", "#812581", "#b5367a", "#e55064", "#fb8761", "#fec287", "#fcfdbf"], GradientSpace::Oklab),
+                ColourPalette::from_hex("Sunset", &["#2d1b4e", "#7b2d6b", "#d1495b", "#f08a4b", "#f6d55c"], GradientSpace::Oklch),
+                ColourPalette::from_hex("Ocean", &["#03045e", "#0077b6", "#00b4d8", "#90e0ef", "#caf0f8"], GradientSpace::Oklab),
+                ColourPalette::from_hex("Greyscale", &["#000000", "#ffffff"], GradientSpace::Oklab),
+            ],
+            active: 0,
+        }
+    }
+}
+
+impl Palettes {
+    pub(crate) fn active(&self) -> &ColourPalette {
+        &self.all[self.active]
+    }
+
+    /// Makes `palette` the active one, replacing any palette with the same name
+    pub(crate) fn add(&mut self, palette: ColourPalette) {
+        match self.all.iter().position(|x| x.name == palette.name) {
+            Some(i) => {
+                self.all[i] = palette;
+                self.active = i;
+            }
+            None => {
+                self.all.push(palette);
+                self.active = self.all.len() - 1;
+            }
+        }
+    }
+
+    pub(crate) fn show(&mut self, ui: &mut Ui) {
+        egui::ComboBox::from_label("Palette")
+            .selected_text(self.active().name.clone())
+            .show_ui(ui, |ui| {
+                for (i, palette) in self.all.iter().enumerate() {
+                    ui.selectable_value(&mut self.active, i, palette.name.clone());
+                }
+            });
+
+        let active = &mut self.all[self.active];
+        show_choice(ui, "Blend in", &mut active.space);
+
+        // preview strip
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 16.0), egui::Sense::hover());
+        let steps = 64;
+        for i in 0..steps {
+            let left = rect.left() + rect.width() * i as f32 / steps as f32;
+            let right = rect.left() + rect.width() * (i + 1) as f32 / steps as f32;
+            let c = active.sample((i as f32 + 0.5) / steps as f32);
+            ui.painter().rect_filled(
+                egui::Rect::from_min_max(egui::pos2(left, rect.top()), egui::pos2(right, rect.bottom())),
+                0.0,
+                egui::Color32::from(egui::Rgba::from_rgb(c.red, c.green, c.blue)),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(colour: palette::Srgb<f32>) -> [u8; 3] {
+        colour.into_format::<u8>().into()
+    }
+
+    #[test]
+    fn reads_gpl() {
+        let text = "GIMP Palette\nName: Sunrise\nColumns: 2\n# a comment\n255 0 0\tRed\n  0 128 255 Sky\n\nnot a colour\n";
+        let (name, colours) = parse_gpl(text).unwrap();
+        assert_eq!(name.as_deref(), Some("Sunrise"));
+        assert_eq!(colours.into_iter().map(bytes).collect::<Vec<[u8; 3]>>(), vec![[255, 0, 0], [0, 128, 255]]);
+
+        assert!(parse_gpl("255 0 0 Red\n").is_err());
+    }
+
+    /// One ASE block, with the type, length and body laid out as in the file
+    fn ase_block(block_type: u16, body: &[u8]) -> Vec<u8> {
+        let mut block = block_type.to_be_bytes().to_vec();
+        block.extend((body.len() as u32).to_be_bytes());
+        block.extend(body);
+        block
+    }
+
+    fn ase_colour(name: &str, model: &[u8; 4], values: &[f32]) -> Vec<u8> {
+        let name = name.encode_utf16().chain([0]).collect::<Vec<u16>>();
+        let mut body = (name.len() as u16).to_be_bytes().to_vec();
+        body.extend(name.iter().flat_map(|x| x.to_be_bytes()));
+        body.extend(model);
+        body.extend(values.iter().flat_map(|x| x.to_be_bytes()));
+        // global, spot or normal swatch
+        body.extend(2_u16.to_be_bytes());
+        ase_block(0x0001, &body)
+    }
+
+    #[test]
+    fn reads_ase() {
+        let blocks = [
+            ase_block(0xc001, &[0, 2, 0, b'G', 0, 0]),
+            ase_colour("Red", b"RGB ", &[1.0, 0.0, 0.0]),
+            ase_colour("Grey", b"Gray", &[0.5]),
+            ase_colour("Cyan", b"CMYK", &[1.0, 0.0, 0.0, 0.0]),
+            ase_colour("Lab", b"LAB ", &[50.0, 0.0, 0.0]),
+            ase_block(0xc002, &[]),
+        ];
+        let mut file = b"ASEF".to_vec();
+        file.extend(1_u16.to_be_bytes());
+        file.extend(0_u16.to_be_bytes());
+        file.extend((blocks.len() as u32).to_be_bytes());
+        file.extend(blocks.concat());
+
+        let colours = parse_ase(&file).unwrap();
+        assert_eq!(colours.into_iter().map(bytes).collect::<Vec<[u8; 3]>>(), vec![[255, 0, 0], [128, 128, 128], [0, 255, 255]]);
+
+        assert!(parse_ase(&file[..file.len() - 20]).is_err());
+        assert!(parse_ase(b"not a swatch file").is_err());
+    }
+
+    #[test]
+    fn reads_json() {
+        let text = r##"{"meta": {"name": "inner"}, "name": "Café \"sol\" 🌅", "colours": ["#ff0000", "#0f0"]}"##;
+        let (name, colours) = parse_json(text).unwrap();
+        assert_eq!(name.as_deref(), Some("Café \"sol\" 🌅"));
+        assert_eq!(colours.into_iter().map(bytes).collect::<Vec<[u8; 3]>>(), vec![[255, 0, 0], [0, 255, 0]]);
+
+        let (name, colours) = parse_json(r##"["#000", "#fff"]"##).unwrap();
+        assert_eq!(name, None);
+        assert_eq!(colours.len(), 2);
+
+        assert!(parse_json(r#"{"name": "\uzzzz"}"#).is_err());
+        assert!(parse_json(r#"{"name": "\ud83c"}"#).is_err());
+        assert!(parse_json(r#"{"name": "unterminated"#).is_err());
+    }
+
+    #[test]
+    fn kmeans_finds_separate_clusters() {
+        let around = |l: f32, a: f32| (0..20).map(move |i| palette::Oklab::new(l + 0.001 * i as f32, a, 0.0));
+        let samples = around(0.2, 0.0).chain(around(0.8, 0.1)).collect::<Vec<palette::Oklab>>();
+
+        let mut centres = kmeans(&samples, 2, 20);
+        centres.sort_by(|x, y| x.l.total_cmp(&y.l));
+        assert_eq!(centres.len(), 2);
+        assert!((centres[0].l - 0.2095).abs() < 1e-3 && centres[0].a.abs() < 1e-3);
+        assert!((centres[1].l - 0.8095).abs() < 1e-3 && (centres[1].a - 0.1).abs() < 1e-3);
+
+        // asking for more clusters than there are distinct colours
+        assert_eq!(kmeans(&[palette::Oklab::new(0.5, 0.0, 0.0); 4], 3, 10).len(), 1);
+    }
+}
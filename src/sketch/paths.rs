@@ -0,0 +1,253 @@
+use std::collections::BinaryHeap;
+use crate::sketch::*;
+
+pub(crate) const SHORTEST_PATH_RENDER_MODE: u8 = 15;
+
+/// The points a path runs between, by id so they survive points being added and removed.
+/// The source is also the root of the BFS and DFS trees.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) struct PathEnds {
+    pub source: Option<usize>,
+    pub target: Option<usize>,
+}
+
+impl PathEnds {
+    fn indices(&self, points: &[Point]) -> Option<(usize, usize)> {
+        let index = |id: Option<usize>| id.and_then(|id| points.iter().position(|p| p.id == id));
+        Some((index(self.source)?, index(self.target)?))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum PathSearch {
+    Dijkstra,
+    /// Dijkstra steered by the straight line distance left to the target
+    AStar,
+}
+
+impl SettingsChoice for PathSearch {
+    const ALL: &'static [Self] = &[PathSearch::Dijkstra, PathSearch::AStar];
+
+    fn name(&self) -> &'static str {
+        match self {
+            PathSearch::Dijkstra => "Dijkstra",
+            PathSearch::AStar => "A*",
+        }
+    }
+}
+
+/// Everything the search did, in the order it did it, so the frontier can be played back
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PathSearchResult {
+    /// Path length from the source for every point that was reached
+    pub distance: Vec<f32>,
+    pub parent: Vec<Option<usize>>,
+    /// The step each point was first reached on, `usize::MAX` if never
+    pub discovered_at: Vec<usize>,
+    /// The step each point's shortest distance became final on, `usize::MAX` if never
+    pub settled_at: Vec<usize>,
+    pub steps: usize,
+    /// Source to target, empty if the target can't be reached
+    pub path: Vec<usize>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct QueueEntry {
+    priority: f32,
+    point: usize,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueEntry {
+    /// Reversed, so the binary heap pops the lowest priority first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.partial_cmp(&self.priority).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Shortest path over the graph with edges weighted by their length, stopping once the target is settled
+pub(crate) fn shortest_path(points: &[Vec2], neighbours: &[Vec<usize>], source: usize, target: usize, search: PathSearch) -> PathSearchResult {
+    let n = points.len();
+    let mut result = PathSearchResult {
+        distance: vec![f32::INFINITY; n],
+        parent: vec![None; n],
+        discovered_at: vec![usize::MAX; n],
+        settled_at: vec![usize::MAX; n],
+        steps: 0,
+        path: vec![],
+    };
+    // straight line distance never overestimates, so A* still finds the shortest path
+    let heuristic = |i: usize| match search {
+        PathSearch::Dijkstra => 0.0,
+        PathSearch::AStar => points[i].distance(points[target]),
+    };
+
+    let mut queue = BinaryHeap::new();
+    result.distance[source] = 0.0;
+    result.discovered_at[source] = 0;
+    queue.push(QueueEntry { priority: heuristic(source), point: source });
+
+    while let Some(QueueEntry { point, .. }) = queue.pop() {
+        // stale entries from before the distance was improved
+        if result.settled_at[point] != usize::MAX {
+            continue;
+        }
+        result.steps += 1;
+        result.settled_at[point] = result.steps;
+        if point == target {
+            break;
+        }
+
+        for &neighbour in neighbours[point].iter() {
+            let distance = result.distance[point] + points[point].distance(points[neighbour]);
+            if distance < result.distance[neighbour] {
+                result.distance[neighbour] = distance;
+                result.parent[neighbour] = Some(point);
+                if result.discovered_at[neighbour] == usize::MAX {
+                    result.discovered_at[neighbour] = result.steps;
+                }
+                queue.push(QueueEntry { priority: distance + heuristic(neighbour), point: neighbour });
+            }
+        }
+    }
+
+    if result.settled_at[target] != usize::MAX {
+        let mut current = Some(target);
+        while let Some(point) = current {
+            result.path.push(point);
+            current = result.parent[point];
+        }
+        result.path.reverse();
+    }
+    result
+}
+
+/// How far through the search the animation has got, started again whenever what is being searched changes
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PathAnimation {
+    pub shown_steps: f32,
+    searched: Option<(PathEnds, PathSearch)>,
+}
+
+pub(crate) fn update_path_animation(model: &mut Model, dt: f32) {
+    // starts from the source again the next time the mode is opened
+    if model.settings.render_mode != SHORTEST_PATH_RENDER_MODE {
+        model.path_animation = PathAnimation::default();
+        return;
+    }
+    let key = Some((model.path_ends, model.settings.path_search));
+    if model.path_animation.searched != key {
+        model.path_animation = PathAnimation { shown_steps: 0.0, searched: key };
+    }
+    model.path_animation.shown_steps += model.settings.for_mode(SHORTEST_PATH_RENDER_MODE)[0].value_f32() * dt;
+}
+
+/// Searches between the chosen ends with the points where they are this frame
+pub(crate) fn current_path(model: &Model) -> Option<PathSearchResult> {
+    let (source, target) = model.path_ends.indices(&model.points)?;
+    let points = model.points.iter().map(|p| p.pos).collect::<Vec<Vec2>>();
+    let neighbours = delaunay::delaunay_neighbours(model);
+    Some(shortest_path(&points, &neighbours, source, target, model.settings.path_search))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scattered points from a fixed seed with each joined both ways to its closest few
+    fn scattered_graph(n: usize, k: usize) -> (Vec<Vec2>, Vec<Vec<usize>>) {
+        let mut state: u32 = 77;
+        let mut next = move || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32
+        };
+        let points = (0..n).map(|_| Vec2::new(next() * 400.0, next() * 300.0)).collect::<Vec<Vec2>>();
+
+        let mut neighbours = vec![vec![]; n];
+        for i in 0..n {
+            let mut others = (0..n).filter(|&j| j != i).collect::<Vec<usize>>();
+            others.sort_by(|&a, &b| points[i].distance(points[a]).total_cmp(&points[i].distance(points[b])));
+            for &j in &others[..k] {
+                if !neighbours[i].contains(&j) {
+                    neighbours[i].push(j);
+                    neighbours[j].push(i);
+                }
+            }
+        }
+        (points, neighbours)
+    }
+
+    /// Shortest distances from `source` by relaxing every edge until nothing changes
+    fn bellman_ford(points: &[Vec2], neighbours: &[Vec<usize>], source: usize) -> Vec<f32> {
+        let mut distance = vec![f32::INFINITY; points.len()];
+        distance[source] = 0.0;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (a, around) in neighbours.iter().enumerate() {
+                for &b in around {
+                    let through = distance[a] + points[a].distance(points[b]);
+                    if through < distance[b] - 1e-3 {
+                        distance[b] = through;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        distance
+    }
+
+    fn path_length(points: &[Vec2], path: &[usize]) -> f32 {
+        path.windows(2).map(|x| points[x[0]].distance(points[x[1]])).sum()
+    }
+
+    #[test]
+    fn finds_the_shortest_path() {
+        let (points, neighbours) = scattered_graph(60, 3);
+        let expected = bellman_ford(&points, &neighbours, 0);
+
+        for target in (1..points.len()).filter(|&t| expected[t].is_finite()) {
+            for search in [PathSearch::Dijkstra, PathSearch::AStar] {
+                let result = shortest_path(&points, &neighbours, 0, target, search);
+                assert_eq!(result.path.first(), Some(&0));
+                assert_eq!(result.path.last(), Some(&target));
+                assert!(result.path.windows(2).all(|x| neighbours[x[0]].contains(&x[1])));
+                assert!((path_length(&points, &result.path) - expected[target]).abs() < 1e-2, "{:?} to {}", search, target);
+                assert!((result.distance[target] - expected[target]).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn a_star_settles_no_more_than_dijkstra() {
+        let (points, neighbours) = scattered_graph(60, 3);
+        let dijkstra = shortest_path(&points, &neighbours, 0, 59, PathSearch::Dijkstra);
+        let a_star = shortest_path(&points, &neighbours, 0, 59, PathSearch::AStar);
+        assert!(a_star.steps <= dijkstra.steps);
+    }
+
+    #[test]
+    fn unreachable_target_has_no_path() {
+        // two pairs with nothing between them
+        let points = vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(5.0, 0.0), Vec2::new(6.0, 0.0)];
+        let neighbours = vec![vec![1], vec![0], vec![3], vec![2]];
+        let result = shortest_path(&points, &neighbours, 0, 3, PathSearch::Dijkstra);
+        assert!(result.path.is_empty());
+        assert_eq!(result.settled_at[2], usize::MAX);
+        assert_eq!(result.discovered_at[3], usize::MAX);
+        assert!(result.distance[3].is_infinite());
+    }
+}
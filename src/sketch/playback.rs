@@ -0,0 +1,154 @@
+use crate::sketch::*;
+use crate::sketch::delaunay::{delaunay_edges, delaunay_triangulation, triangulate_recorded, TriangulationEvent};
+use crate::sketch::kruskals::{kruskals_steps, Edge};
+use crate::sketch::nearest::{bfs_steps, BfsStep};
+
+pub(crate) const PLAYBACK_RENDER_MODE: u8 = 16;
+
+/// Which algorithm gets recorded and played back step by step
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum PlaybackAlgorithm {
+    Kruskal,
+    Bfs,
+    Triangulation,
+}
+
+impl SettingsChoice for PlaybackAlgorithm {
+    const ALL: &'static [Self] = &[PlaybackAlgorithm::Kruskal, PlaybackAlgorithm::Bfs, PlaybackAlgorithm::Triangulation];
+
+    fn name(&self) -> &'static str {
+        match self {
+            PlaybackAlgorithm::Kruskal => "Kruskal's spanning tree",
+            PlaybackAlgorithm::Bfs => "Breadth first search",
+            PlaybackAlgorithm::Triangulation => "Delaunay triangulation",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum RecordedSteps {
+    /// Edges in the order they were looked at, and whether each was kept, by index into the recorded points
+    Kruskal(Vec<(Edge, bool)>),
+    Bfs(Vec<BfsStep>),
+    /// Indices are into the points that were recorded
+    Triangulation(Vec<TriangulationEvent>),
+}
+
+impl RecordedSteps {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            RecordedSteps::Kruskal(steps) => steps.len(),
+            RecordedSteps::Bfs(steps) => steps.len(),
+            RecordedSteps::Triangulation(steps) => steps.len(),
+        }
+    }
+}
+
+/// A recording of one run of an algorithm over the points as they were when it was made.
+/// The points stay still and can't be painted or dragged while the playback render mode is open,
+/// it is recorded again when it is opened, or when points are added or removed from the settings.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Playback {
+    pub recording: Option<(PlaybackAlgorithm, RecordedSteps)>,
+    /// The points the recording was made with
+    pub points: Vec<Point>,
+    /// Id of the point the search started from
+    source: Option<usize>,
+    pub shown_steps: f32,
+    pub playing: bool,
+}
+
+impl Playback {
+    fn record(app: &App, model: &Model, algorithm: PlaybackAlgorithm) -> Playback {
+        let edges = || delaunay_triangulation(app, model, false).iter().flat_map(|x| {
+            [(x.0, x.1), (x.1, x.2), (x.2, x.0)]
+        }).collect::<Vec<(Point, Point)>>();
+
+        let steps = match algorithm {
+            PlaybackAlgorithm::Kruskal => {
+                let points = model.points.iter().map(|p| p.pos).collect::<Vec<Vec2>>();
+                RecordedSteps::Kruskal(kruskals_steps(&points, &delaunay_edges(model)))
+            }
+            PlaybackAlgorithm::Bfs => RecordedSteps::Bfs(bfs_steps(edges(), model.path_ends.source)),
+            PlaybackAlgorithm::Triangulation => {
+                let x = model.points.iter().map(|p| Point64::from(*p)).collect::<Vec<Point64>>();
+                RecordedSteps::Triangulation(triangulate_recorded(&x).events.unwrap_or_default())
+            }
+        };
+
+        Playback {
+            recording: Some((algorithm, steps)),
+            points: model.points.clone(),
+            source: model.path_ends.source,
+            shown_steps: 0.0,
+            playing: true,
+        }
+    }
+
+    /// Whether the recording was made with the algorithm, points and search source there are now
+    fn matches(&self, model: &Model) -> bool {
+        let algorithm = self.recording.as_ref().map(|(a, _)| *a);
+        algorithm == Some(model.settings.playback_algorithm)
+            && self.source == model.path_ends.source
+            && self.points.len() == model.points.len()
+            && self.points.iter().zip(model.points.iter()).all(|(a, b)| a.id == b.id)
+    }
+
+    pub(crate) fn total_steps(&self) -> usize {
+        self.recording.as_ref().map_or(0, |(_, steps)| steps.len())
+    }
+
+    /// How many steps have been carried out so far, the last of them is the one being shown
+    pub(crate) fn current_step(&self) -> usize {
+        (self.shown_steps as usize).min(self.total_steps())
+    }
+
+    pub(crate) fn show_controls(&mut self, ui: &mut Ui) {
+        let total = self.total_steps();
+        ui.horizontal(|ui| {
+            if ui.button("Restart").clicked() {
+                self.shown_steps = 0.0;
+            }
+            if ui.button("Step back").clicked() {
+                self.playing = false;
+                self.shown_steps = self.current_step().saturating_sub(1) as f32;
+            }
+            let label = if self.playing { "Pause" } else { "Play" };
+            if ui.button(label).clicked() {
+                if !self.playing && self.current_step() >= total {
+                    self.shown_steps = 0.0;
+                }
+                self.playing = !self.playing;
+            }
+            if ui.button("Step forward").clicked() {
+                self.playing = false;
+                self.shown_steps = (self.current_step() + 1).min(total) as f32;
+            }
+        });
+        ui.label(format!("Step {} of {}", self.current_step(), total));
+        if ui.button("Record again").clicked() {
+            self.recording = None;
+        }
+    }
+}
+
+pub(crate) fn update_playback(app: &App, model: &mut Model, dt: f32) {
+    if model.settings.render_mode != PLAYBACK_RENDER_MODE {
+        // the points move on once the playback is closed, so it gets recorded afresh next time
+        model.playback = Playback::default();
+        return;
+    }
+
+    if !model.playback.matches(model) {
+        model.playback = Playback::record(app, model, model.settings.playback_algorithm);
+    }
+
+    if model.playback.playing {
+        let total = model.playback.total_steps() as f32;
+        model.playback.shown_steps += model.settings.for_mode(PLAYBACK_RENDER_MODE)[0].value_f32() * dt;
+        if model.playback.shown_steps >= total {
+            model.playback.shown_steps = total;
+            model.playback.playing = false;
+        }
+    }
+}
@@ -8,10 +8,55 @@ use nannou_egui::egui::include_image;
 pub(crate) struct Point {
     pub pos: Vec2,
     pub moving_vec: Vec2,
+    /// What gets drawn, worked out again every frame unless colouring by the original colour
     pub colour: LinSrgb<f32>,
+    /// The colour it was generated or painted with
+    pub base_colour: LinSrgb<f32>,
     pub id: usize,
     pub starting_location: Vec2,
     pub last_pos: Vec2,
+    /// Pinned points ignore every force and are never wrapped
+    pub pinned: bool,
+    /// Reaction-diffusion state of the point's cell
+    pub reagents: Reagents,
+    /// Cellular automaton state of the point's cell
+    pub life: Life,
+}
+
+pub(crate) const TRAIL_CAPACITY: usize = 64;
+
+/// Fixed size ring buffer of past positions, the oldest gets overwritten first.
+/// Kept on the model by point id rather than in `Point`, which gets copied and compared all over.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Trail {
+    positions: [Vec2; TRAIL_CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl Trail {
+    pub(crate) fn new() -> Trail {
+        Trail {
+            positions: [Vec2::ZERO; TRAIL_CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, pos: Vec2) {
+        self.positions[self.head] = pos;
+        self.head = (self.head + 1) % TRAIL_CAPACITY;
+        self.len = (self.len + 1).min(TRAIL_CAPACITY);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Up to `n` of the most recent positions, newest first
+    pub(crate) fn recent(&self, n: usize) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.len.min(n)).map(move |i| self.positions[(self.head + TRAIL_CAPACITY - 1 - i) % TRAIL_CAPACITY])
+    }
 }
 
 
@@ -34,14 +79,18 @@ impl Point {
             pos,
             moving_vec: Vec2::ZERO,
             colour,
+            base_colour: colour,
             id: random(),
             starting_location: pos,
             last_pos: pos,
+            pinned: false,
+            reagents: Reagents::default(),
+            life: Life::default(),
         }
     }
 
 
-    pub(crate) fn new_points_circle(_app: &App) -> Vec<Point> {
+    pub(crate) fn new_points_circle(_world: Rect, palette: &ColourPalette) -> Vec<Point> {
 
         let mut points = Vec::new();
         let angles = [0.25 * PI, 0.75 * PI, 1.25 * PI, 1.75 * PI];
@@ -52,22 +101,22 @@ impl Point {
             let dist = prop * POINTS_SQUARE_WIDTH_PX as f32 / 2.0;
             let (x, y) = (dist * angle.cos(), dist * angle.sin());
 
-            let point = Point::new(Vec2::new(x, y), colour_from_zero_one(prop));
+            let point = Point::new(Vec2::new(x, y), palette.sample(prop));
             points.push(point)
         }
 
         points
     }
 
-    pub(crate) fn new_points(app: &App) -> Vec<Point> {
-        Point::new_points_square(app)
+    pub(crate) fn new_points(world: Rect, palette: &ColourPalette) -> Vec<Point> {
+        Point::new_points_square(world, palette)
     }
 
 
-    pub(crate) fn new_points_from_image(app: &App) -> Vec<Point> {
+    pub(crate) fn new_points_from_image(world: Rect) -> Vec<Point> {
         let mut points = vec![];
         let max = 50;
-        let wh = app.window_rect().wh();
+        let wh = world.wh();
         let image = image::load_from_memory(include_bytes!("../../puppy_blur.jpg")).unwrap();;
         let image_wh = Vec2::new(image.width() as f32, image.height() as f32);
 
@@ -75,7 +124,7 @@ impl Point {
             let prop_y = (y as f32 + 0.5) / max as f32;
             for x in 0..max {
                 let prop_x = (x as f32 + 0.5) / max as f32;
-                let pos = Vec2::new(( 0.5 - prop_x) * wh.x, (0.5 - prop_y) * wh.y);
+                let pos = world.xy() + Vec2::new(( 0.5 - prop_x) * wh.x, (0.5 - prop_y) * wh.y);
                 let image_data = image.get_pixel((prop_x * image_wh.x).round() as u32, (prop_y * image_wh.y).round() as u32);
                 let colour:LinSrgb<f32> = convert(image_data);
 
@@ -87,17 +136,36 @@ impl Point {
         points
     }
 
-    pub(crate) fn new_points_square(app: &App) -> Vec<Point> {
+    /// Rejection samples `count` points so that darker parts of the image get more of them
+    pub(crate) fn new_points_stipple(world: Rect, image: &ImageField, count: u32) -> Vec<Point> {
+        let rect = world;
         let mut points = vec![];
 
-        let rect = app.window_rect();
+        // bounded so a blank image can't hang the sketch
+        for _ in 0..count * 100 {
+            if points.len() >= count as usize {
+                break;
+            }
+            let pos = Vec2::new(random_range(rect.left(), rect.right()), random_range(rect.bottom(), rect.top()));
+            if random_f32() < image.darkness(rect, pos) {
+                points.push(Point::new(pos, image.colour(rect, pos)));
+            }
+        }
+
+        points
+    }
+
+    pub(crate) fn new_points_square(world: Rect, palette: &ColourPalette) -> Vec<Point> {
+        let mut points = vec![];
+
+        let rect = world;
         let n_x = 30.;
         let spacing_x = rect.x.len() / n_x;
         let n_y = (rect.y.len() / spacing_x).floor();
         let spacing_y = rect.y.len() / n_y;
         let d = Vec2::new(spacing_x, spacing_y);
         let start = rect.bottom_left() + d / 2.0;
-        let colour_fn = |x, y| colour_from_zero_one(((x as f32 / n_x) + y as f32) / n_y);
+        let colour_fn = |x, y| palette.sample(((x as f32 / n_x) + y as f32) / n_y);
         for i in 0..(n_x as i32) {
             for j in 0..(n_y as i32) {
                 points.push(Point::new(start + d * IVec2::new(i, j).as_f32(), colour_fn(i, j)))
@@ -108,12 +176,12 @@ impl Point {
         points
     }
 
-    pub(crate) fn new_points_multi_colour_spiral(app: &App) -> Vec<Point> {
+    pub(crate) fn new_points_multi_colour_spiral(world: Rect, palette: &ColourPalette) -> Vec<Point> {
         let max_angle = TAU;
         let n_colours:usize = 5;
         let mut points = vec![];
 
-        let max_radius = app.window_rect().wh().max_element() / 2.0;
+        let max_radius = world.wh().max_element() / 2.0;
 
         let a = max_radius / max_angle;
         for spiral_num in 0..n_colours {
@@ -123,15 +191,15 @@ impl Point {
             for point_num in 1..=num_points {
                 let individual_spiral_proportion = (point_num as f32 - 1.0) / num_points as f32;
                 let t = point_num as f32 / num_points as f32 * max_angle;
-                let pos = Vec2::new(a * t * (t + offset_angle).cos(), a * t * (t + offset_angle).sin());
+                let pos = world.xy() + Vec2::new(a * t * (t + offset_angle).cos(), a * t * (t + offset_angle).sin());
 
                 // let hue = (offset_proportion * 6.0).round() / 6.0;
                 let hue = offset_proportion;
 
                 let saturation = 1.0 - individual_spiral_proportion * 0.9;
-                let colour = okhsv_to_linsrgb(hue, saturation, 1.0);
+                let colour = palette.sample_tinted(hue, 1.0 - saturation);
 
-                if app.window_rect().contains(pos) {
+                if world.contains(pos) {
                     let point = Point::new(pos, colour);
                     points.push(point)
                 }
@@ -167,7 +235,7 @@ impl Point {
     // }
 }
 
-fn convert(x: image::Rgba<u8>) -> LinSrgb {
+pub(crate) fn convert(x: image::Rgba<u8>) -> LinSrgb {
     let c = nannou::color::Rgba::new(x.0[0], x.0[1], x.0[2], 255);
     let c2 = c.into_lin_srgba();
     c2.into()
@@ -199,12 +267,13 @@ impl From<[f32; 2]> for Point64 {
     }
 }
 
+pub(crate) fn nearest_point(points: &[Point], pos: Vec2) -> Option<usize> {
+    points.iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.pos.distance(pos).partial_cmp(&b.pos.distance(pos)).unwrap_or(Ordering::Equal))
+        .map(|(i, _)| i)
+}
+
 pub(crate) fn vec2_is_wrong(pos: &Vec2) -> bool {
     !(pos.x.is_finite() && pos.y.is_finite())
 }
-
-pub(crate) fn distance_to_line(center: Vec2, p1: Vec2, p2: Vec2) -> f32 {
-    let num = ((p2.y - p1.y) * center.x - (p2.x - p1.x) * center.y + p2.x * p1.y - p2.y * p1.x).abs();
-    let denom = ((p2.y - p1.y).powi(2) + (p2.x - p1.x).powi(2)).sqrt();
-    num / denom
-}
\ No newline at end of file
@@ -0,0 +1,196 @@
+use crate::sketch::*;
+use crate::sketch::delaunay::{edges_of, neighbours_of, triangles_of};
+
+pub(crate) const PROXIMITY_RENDER_MODE: u8 = 12;
+
+/// Graphs that join points which are close in some sense, all but k-NN are subgraphs of the Delaunay triangulation
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum ProximityGraph {
+    /// Edges whose diametral circle holds no other point
+    Gabriel,
+    /// Edges with no point closer to both ends than they are to each other
+    RelativeNeighbourhood,
+    /// Delaunay without the longest edge of every triangle
+    Urquhart,
+    /// Every point joined to its k nearest
+    KNearest,
+    /// Edges whose lune, sized by beta, holds no other point. Beta 1 is Gabriel and 2 is the relative neighbourhood graph.
+    BetaSkeleton,
+}
+
+impl SettingsChoice for ProximityGraph {
+    const ALL: &'static [Self] = &[
+        ProximityGraph::Gabriel,
+        ProximityGraph::RelativeNeighbourhood,
+        ProximityGraph::Urquhart,
+        ProximityGraph::KNearest,
+        ProximityGraph::BetaSkeleton,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ProximityGraph::Gabriel => "Gabriel graph",
+            ProximityGraph::RelativeNeighbourhood => "Relative neighbourhood graph",
+            ProximityGraph::Urquhart => "Urquhart graph",
+            ProximityGraph::KNearest => "k nearest neighbours",
+            ProximityGraph::BetaSkeleton => "Beta skeleton",
+        }
+    }
+}
+
+/// Edges of the chosen graph as indices into `model.points`, smaller index first
+pub(crate) fn proximity_edges(model: &Model, graph: ProximityGraph, k: usize, beta: f32) -> Vec<(usize, usize)> {
+    graph_edges(&model.points, graph, k, beta)
+}
+
+fn graph_edges(points: &[Point], graph: ProximityGraph, k: usize, beta: f32) -> Vec<(usize, usize)> {
+    match graph {
+        ProximityGraph::Gabriel => lune_edges(points, 1.0),
+        ProximityGraph::RelativeNeighbourhood => lune_edges(points, 2.0),
+        ProximityGraph::BetaSkeleton => lune_edges(points, beta),
+        ProximityGraph::Urquhart => urquhart_edges(points),
+        ProximityGraph::KNearest => k_nearest_edges(points, k),
+    }
+}
+
+/// Whether `c` is strictly inside the lune of `a` and `b` for a lune based beta skeleton with beta of at least 1
+fn in_lune(a: Vec2, b: Vec2, c: Vec2, beta: f32) -> bool {
+    let radius = beta * a.distance(b) / 2.0;
+    let centre_a = a.lerp(b, beta / 2.0);
+    let centre_b = b.lerp(a, beta / 2.0);
+    c.distance(centre_a) < radius && c.distance(centre_b) < radius
+}
+
+/// Delaunay edges with empty lunes, every edge of the skeleton is one for beta of at least 1.
+/// Up to beta 2 a non-empty lune always holds a Delaunay neighbour of one of the ends, so only those
+/// need testing. Wider lunes can reach past them, so beyond 2 every point is tested, brute force.
+fn lune_edges(points: &[Point], beta: f32) -> Vec<(usize, usize)> {
+    let neighbours = neighbours_of(points);
+    let pos = |i: usize| points[i].pos;
+    let everyone = (0..points.len()).collect::<Vec<usize>>();
+
+    edges_of(points).into_iter().filter(|&(a, b)| {
+        let candidates = if beta <= 2.0 {
+            neighbours[a].iter().chain(neighbours[b].iter())
+        } else {
+            everyone.iter().chain(&[])
+        };
+        !candidates
+            .filter(|&&c| c != a && c != b)
+            .any(|&c| in_lune(pos(a), pos(b), pos(c), beta))
+    }).collect()
+}
+
+fn urquhart_edges(points: &[Point]) -> Vec<(usize, usize)> {
+    let pos = |i: usize| points[i].pos;
+    let ordered = |(a, b): (usize, usize)| (a.min(b), a.max(b));
+
+    let triangles = triangles_of(points);
+    let longest = triangles.iter().map(|t| {
+        let edges = [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])];
+        let longest = edges.into_iter()
+            .max_by(|x, y| pos(x.0).distance(pos(x.1)).partial_cmp(&pos(y.0).distance(pos(y.1))).unwrap_or(Ordering::Equal))
+            .unwrap();
+        ordered(longest)
+    }).collect::<HashSet<(usize, usize)>>();
+
+    edges_of(points).into_iter().filter(|e| !longest.contains(e)).collect()
+}
+
+/// Brute force, which is fine for the few thousand points the sketch runs with
+fn k_nearest_edges(points: &[Point], k: usize) -> Vec<(usize, usize)> {
+    let mut edges = vec![];
+    for (i, point) in points.iter().enumerate() {
+        let mut others = points.iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(j, other)| (j, point.pos.distance_squared(other.pos)))
+            .collect::<Vec<(usize, f32)>>();
+        let k = k.min(others.len());
+        if k == 0 {
+            continue;
+        }
+        others.select_nth_unstable_by(k - 1, |a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+        edges.extend(others[..k].iter().map(|(j, _)| (i.min(*j), i.max(*j))));
+    }
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(positions: &[Vec2]) -> Vec<Point> {
+        positions.iter().map(|&pos| Point::new(pos, lin_srgb(1.0, 1.0, 1.0))).collect()
+    }
+
+    /// Scattered points from a fixed seed, so failures can be reproduced
+    fn scattered(n: usize) -> Vec<Point> {
+        let mut state: u32 = 2024;
+        let mut next = move || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32
+        };
+        points(&(0..n).map(|_| Vec2::new(next() * 400.0, next() * 300.0)).collect::<Vec<Vec2>>())
+    }
+
+    /// Every pair with an empty lune, checked against every other point
+    fn brute_force_skeleton(points: &[Point], beta: f32) -> Vec<(usize, usize)> {
+        let n = points.len();
+        (0..n)
+            .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+            .filter(|&(a, b)| (0..n).filter(|&c| c != a && c != b).all(|c| !in_lune(points[a].pos, points[b].pos, points[c].pos, beta)))
+            .collect()
+    }
+
+    #[test]
+    fn skeletons_match_brute_force() {
+        let points = scattered(40);
+        for (graph, beta) in [(ProximityGraph::Gabriel, 1.0), (ProximityGraph::RelativeNeighbourhood, 2.0)] {
+            assert_eq!(graph_edges(&points, graph, 0, 0.0), brute_force_skeleton(&points, beta), "{:?}", graph);
+        }
+        for beta in [1.0, 1.5, 2.0, 2.5, 3.0] {
+            assert_eq!(graph_edges(&points, ProximityGraph::BetaSkeleton, 0, beta), brute_force_skeleton(&points, beta), "beta {}", beta);
+        }
+    }
+
+    #[test]
+    fn wide_lunes_reach_past_the_delaunay_neighbours() {
+        // c sits in the beta 3 lune of ab, but only d and e are Delaunay neighbours of a and b
+        let points = points(&[
+            Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(0.5, 1.0),
+            Vec2::new(-0.05, 0.494), Vec2::new(1.05, 0.494),
+        ]);
+        assert!(!graph_edges(&points, ProximityGraph::BetaSkeleton, 0, 3.0).contains(&(0, 1)));
+    }
+
+    #[test]
+    fn graphs_nest() {
+        let points = scattered(40);
+        let edges = |graph| graph_edges(&points, graph, 0, 0.0).into_iter().collect::<HashSet<(usize, usize)>>();
+        let (gabriel, relative, urquhart) = (edges(ProximityGraph::Gabriel), edges(ProximityGraph::RelativeNeighbourhood), edges(ProximityGraph::Urquhart));
+        let delaunay = edges_of(&points).into_iter().collect::<HashSet<(usize, usize)>>();
+
+        assert!(relative.is_subset(&gabriel));
+        assert!(relative.is_subset(&urquhart));
+        assert!(gabriel.is_subset(&delaunay));
+        assert!(urquhart.is_subset(&delaunay));
+    }
+
+    #[test]
+    fn k_nearest_joins_each_point_to_its_closest() {
+        let points = scattered(30);
+        let k = 3;
+        let edges = graph_edges(&points, ProximityGraph::KNearest, k, 0.0);
+        for (i, point) in points.iter().enumerate() {
+            let mut others = (0..points.len()).filter(|&j| j != i).collect::<Vec<usize>>();
+            others.sort_by(|&a, &b| point.pos.distance(points[a].pos).total_cmp(&point.pos.distance(points[b].pos)));
+            for &j in &others[..k] {
+                assert!(edges.contains(&(i.min(j), i.max(j))));
+            }
+        }
+        assert!(edges.windows(2).all(|x| x[0] < x[1]));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::sketch::*;
+use crate::sketch::delaunay::voronoi_edge_lengths;
+
+/// Gray-Scott concentrations carried by each point's cell
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Reagents {
+    /// The substrate that gets fed in
+    pub u: f32,
+    /// The catalyst that turns `u` into more of itself
+    pub v: f32,
+}
+
+impl Default for Reagents {
+    fn default() -> Self {
+        Reagents { u: 1.0, v: 0.0 }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ReactionSettings {
+    pub feed: SettingsItem,
+    pub kill: SettingsItem,
+    pub diffusion_u: SettingsItem,
+    pub diffusion_v: SettingsItem,
+    pub steps_per_frame: SettingsItem,
+}
+
+impl ReactionSettings {
+    pub(crate) fn show(&mut self, ui: &mut Ui) {
+        self.feed.show(ui);
+        self.kill.show(ui);
+        self.diffusion_u.show(ui);
+        self.diffusion_v.show(ui);
+        self.steps_per_frame.show(ui);
+    }
+}
+
+impl Default for ReactionSettings {
+    fn default() -> Self {
+        ReactionSettings {
+            feed: SettingsItem {
+                slider_label: "Feed rate".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 0.055,
+                    range_min: 0.01,
+                    range_max: 0.1,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            kill: SettingsItem {
+                slider_label: "Kill rate".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 0.062,
+                    range_min: 0.03,
+                    range_max: 0.075,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            diffusion_u: SettingsItem {
+                slider_label: "Substrate diffusion".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 1.0,
+                    range_min: 0.0,
+                    range_max: 1.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            diffusion_v: SettingsItem {
+                slider_label: "Catalyst diffusion".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 0.5,
+                    range_min: 0.0,
+                    range_max: 1.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            steps_per_frame: SettingsItem {
+                slider_label: "Reaction steps per frame".to_string(),
+                slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                    value: 8,
+                    range_min: 1,
+                    range_max: 40,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+        }
+    }
+}
+
+/// Drops catalyst into a few random cells and their neighbours
+pub(crate) fn seed_reaction(model: &mut Model) {
+    let neighbours = delaunay::delaunay_neighbours(model);
+    let seeds = (model.points.len() / 20).max(1);
+    for _ in 0..seeds {
+        let i = random_range(0, model.points.len());
+        for j in neighbours[i].iter().copied().chain([i]) {
+            model.points[j].reagents = Reagents { u: 0.5, v: 0.25 };
+        }
+    }
+}
+
+/// Runs Gray-Scott over the cell graph while the points are coloured by it.
+/// Each cell mixes with its neighbours in proportion to the length of the border they share,
+/// normalised so the rates match the usual square grid.
+pub(crate) fn update_reaction_diffusion(model: &mut Model) {
+    if model.settings.colour_by != ColourBy::Concentration || model.points.is_empty() {
+        return;
+    }
+    // a reaction that has died out, or never started, gets going again
+    if model.points.iter().all(|p| p.reagents.v <= f32::EPSILON) {
+        seed_reaction(model);
+    }
+
+    let settings = &model.settings.reaction;
+    let (feed, kill) = (settings.feed.value_f32(), settings.kill.value_f32());
+    let (diffusion_u, diffusion_v) = (settings.diffusion_u.value_f32(), settings.diffusion_v.value_f32());
+
+    let edges = voronoi_edge_lengths(model);
+    let mut total_weight = vec![0.0; model.points.len()];
+    for &(a, b, length) in edges.iter() {
+        total_weight[a] += length;
+        total_weight[b] += length;
+    }
+
+    let mut reagents = model.points.iter().map(|p| p.reagents).collect::<Vec<Reagents>>();
+    for _ in 0..settings.steps_per_frame.value_u32() {
+        let mut laplacian = vec![(0.0, 0.0); reagents.len()];
+        for &(a, b, length) in edges.iter() {
+            let du = reagents[b].u - reagents[a].u;
+            let dv = reagents[b].v - reagents[a].v;
+            laplacian[a].0 += du * length;
+            laplacian[a].1 += dv * length;
+            laplacian[b].0 -= du * length;
+            laplacian[b].1 -= dv * length;
+        }
+
+        for (i, r) in reagents.iter_mut().enumerate() {
+            let (lu, lv) = if total_weight[i] > 0.0 {
+                (laplacian[i].0 / total_weight[i], laplacian[i].1 / total_weight[i])
+            } else {
+                (0.0, 0.0)
+            };
+            let reaction = r.u * r.v * r.v;
+            r.u = (r.u + diffusion_u * lu - reaction + feed * (1.0 - r.u)).clamp(0.0, 1.0);
+            r.v = (r.v + diffusion_v * lv + reaction - (feed + kill) * r.v).clamp(0.0, 1.0);
+        }
+    }
+
+    for (point, r) in model.points.iter_mut().zip(reagents) {
+        point.reagents = r;
+    }
+}
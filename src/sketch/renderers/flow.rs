@@ -0,0 +1,72 @@
+use crate::sketch::*;
+use crate::sketch::renderers::perlin::flow_colour;
+
+pub(crate) const TRAILS_RENDER_MODE: u8 = 10;
+pub(crate) const STREAMLINES_RENDER_MODE: u8 = 11;
+
+/// Fading polylines through the last positions of every point
+pub(crate) fn render_trails(_app: &App, model: &Model, draw: &Draw) {
+    let settings = model.settings.for_mode(TRAILS_RENDER_MODE);
+    let length = settings[0].value_u32() as usize;
+    let fade = if settings[1].bool { settings[1].value_f32() } else { 0.0 };
+    let width = settings[2].value_f32();
+    // a jump this long is a point being wrapped to the other side of the world
+    let max_step = model.world.wh().min_element() / 2.0;
+
+    for point in model.points.iter() {
+        let trail = match model.trails.get(&point.id) {
+            Some(trail) => trail,
+            None => continue,
+        };
+        let c = point.colour;
+        let mut segment: Vec<(Vec2, LinSrgba<f32>)> = vec![];
+        let mut last: Option<Vec2> = None;
+
+        for (age, pos) in trail.recent(length).enumerate() {
+            if last.map_or(false, |last| last.distance(pos) > max_step) {
+                draw_segment(draw, &mut segment, width);
+            }
+            let alpha = 1.0 - fade * age as f32 / length as f32;
+            segment.push((pos, lin_srgba(c.red, c.green, c.blue, alpha)));
+            last = Some(pos);
+        }
+        draw_segment(draw, &mut segment, width);
+    }
+
+    draw_all_points(draw, model);
+}
+
+fn draw_segment(draw: &Draw, segment: &mut Vec<(Vec2, LinSrgba<f32>)>, width: f32) {
+    if segment.len() > 1 {
+        draw.polyline()
+            .weight(width)
+            .points_colored(segment.drain(..));
+    }
+    segment.clear();
+}
+
+/// Evenly spaced streamlines through the flow field, faded towards their ends
+pub(crate) fn render_streamlines(_app: &App, model: &Model, draw: &Draw) {
+    let settings = model.settings.for_mode(STREAMLINES_RENDER_MODE);
+    let fade = if settings[2].bool { settings[2].value_f32() } else { 0.0 };
+    let width = settings[3].value_f32();
+
+    let field = |pos: Vec2| model.noise.flow(model.settings.flow_field, model.world, pos);
+
+    for line in model.streamlines.lines.iter() {
+        let last = (line.len() - 1) as f32;
+        let points = line.iter().enumerate().map(|(i, pos)| {
+            let c = flow_colour(model, *pos, field(*pos));
+            // 0 at either end, 1 in the middle
+            let centre = 1.0 - (2.0 * i as f32 / last - 1.0).abs();
+            let alpha = 1.0 - fade * (1.0 - centre);
+            (*pos, lin_srgba(c.red, c.green, c.blue, alpha))
+        }).collect::<Vec<(Vec2, LinSrgba<f32>)>>();
+
+        draw.polyline()
+            .weight(width)
+            .points_colored(points);
+    }
+
+    draw_all_points(draw, model);
+}
@@ -1,18 +1,40 @@
 use crate::sketch::*;
-use crate::sketch::delaunay::delaunay_triangulation;
+use crate::sketch::delaunay::{delaunay_edges, delaunay_triangulation};
 
-pub(crate) fn render_mst(app: &App, model: &Model, draw: &Draw) {
-    let edges = crate::sketch::delaunay::delaunay_triangulation(app, model, false).iter().flat_map(|x| {
-        [(x.0, x.1), (x.1, x.2), (x.2, x.0)]
-    }).collect::<Vec<(Point, Point)>>();
+pub(crate) fn render_mst(_app: &App, model: &Model, draw: &Draw) {
+    if model.settings.for_mode(MST_RENDER_MODE)[0].bool {
+        render_stable_tree(model, draw);
+        return;
+    }
 
-    let mst = kruskals_mst(edges);
+    let points = model.points.iter().map(|p| p.pos).collect::<Vec<Vec2>>();
+    let mst = minimum_spanning_tree(&points, &delaunay_edges(model), model.settings.mst_algorithm);
 
     for edge in mst.into_iter() {
+        let (start, end) = (&model.points[edge.start], &model.points[edge.end]);
         draw.line()
             .weight(5.0)
-            .points(edge.start.pos, edge.end.pos)
-            .color(average_lin_srgb(&vec![edge.start.colour, edge.end.colour]));
+            .points(start.pos, end.pos)
+            .color(average_colours(&[start.colour, end.colour], model.settings.mix_space));
+    }
+
+    draw_all_points(draw, model);
+}
+
+/// Edges joining or leaving the tree fade and thin in and out
+fn render_stable_tree(model: &Model, draw: &Draw) {
+    let index = model.points.iter().enumerate().map(|(i, p)| (p.id, i)).collect::<HashMap<usize, usize>>();
+    for (&(a, b), edge) in model.stable_tree.edges.iter() {
+        let (start, end) = match (index.get(&a), index.get(&b)) {
+            (Some(&a), Some(&b)) => (&model.points[a], &model.points[b]),
+            _ => continue,
+        };
+        let presence = edge.presence.clamp(0.0, 1.0);
+        let c = average_colours(&[start.colour, end.colour], model.settings.mix_space);
+        draw.line()
+            .weight(5.0 * presence)
+            .points(start.pos, end.pos)
+            .color(lin_srgba(c.red, c.green, c.blue, presence));
     }
 
     draw_all_points(draw, model);
@@ -23,17 +45,164 @@ pub(crate) fn render_bfs(app: &App, model: &Model, draw: &Draw) {
         [(x.0, x.1), (x.1, x.2), (x.2, x.0)]
     }).collect::<Vec<(Point, Point)>>();
 
-    let (edges, distances) = nearest::bfs(edges);
+    let (edges, distances) = nearest::bfs(edges, model.path_ends.source);
+    draw_search_tree(model, draw, edges, distances);
+}
+
+pub(crate) fn render_dfs(app: &App, model: &Model, draw: &Draw) {
+    let edges = delaunay_triangulation(app, model, false).iter().flat_map(|x| {
+        [(x.0, x.1), (x.1, x.2), (x.2, x.0)]
+    }).collect::<Vec<(Point, Point)>>();
+
+    let (edges, depths) = nearest::dfs(edges, model.path_ends.source);
+    draw_search_tree(model, draw, edges, depths);
+}
+
+fn draw_search_tree(model: &Model, draw: &Draw, edges: Vec<(Point, Point)>, distances: Vec<(Point, usize)>) {
+    // coloured by depth through the palette, from the root to the furthest point
+    let max_distance = distances.iter().map(|(_, d)| *d).max().unwrap_or(0).max(1) as f32;
+    let depths = distances.iter().map(|(p, d)| (p.id, *d)).collect::<HashMap<usize, usize>>();
+    let depth_colour = |depth: usize| model.palettes.active().sample_scalar(depth as f32 / max_distance);
+
     for (start, end) in edges {
         draw.line()
             .points(start.pos, end.pos)
             .weight(4.0)
-            .color(average_lin_srgb(&vec![start.colour, end.colour]));
+            .color(average_colours(&[depth_colour(depths[&start.id]), depth_colour(depths[&end.id])], model.settings.mix_space));
     }
 
     if model.settings.show_points.bool {
         for (point, distance) in distances {
-            draw_double_circle(draw, point.pos, point.colour, 12.0 / (distance as f32 + 1.0).powf(0.5) * model.settings.show_points.value_f32());
+            draw_double_circle(draw, point.pos, depth_colour(distance), 12.0 / (distance as f32 + 1.0).powf(0.5) * model.settings.show_points.value_f32());
+        }
+    }
+    draw_path_ends(draw, model);
+}
+
+pub(crate) fn render_proximity_graph(_app: &App, model: &Model, draw: &Draw) {
+    let settings = model.settings.for_mode(PROXIMITY_RENDER_MODE);
+    let k = settings[0].value_u32() as usize;
+    let beta = settings[1].value_f32();
+    let weight = &settings[2];
+
+    let edges = proximity_edges(model, model.settings.proximity_graph, k, beta);
+    let length = |(a, b): (usize, usize)| model.points[a].pos.distance(model.points[b].pos);
+    let longest = edges.iter().map(|e| length(*e)).fold(0.0, f32::max);
+
+    for &(a, b) in edges.iter() {
+        let (start, end) = (&model.points[a], &model.points[b]);
+        let colour = if weight.bool && longest > 0.0 {
+            model.palettes.active().sample_scalar(length((a, b)) / longest)
+        } else {
+            average_colours(&[start.colour, end.colour], model.settings.mix_space)
+        };
+
+        draw.line()
+            .weight(weight.value_f32())
+            .points(start.pos, end.pos)
+            .color(colour);
+    }
+
+    draw_all_points(draw, model);
+}
+
+/// The Delaunay graph, the search spreading out from the source and the shortest path once it reaches the target
+pub(crate) fn render_shortest_path(_app: &App, model: &Model, draw: &Draw) {
+    let settings = model.settings.for_mode(SHORTEST_PATH_RENDER_MODE);
+    let animate = settings[0].bool;
+    let weight = settings[1].value_f32();
+
+    for (a, b) in delaunay::delaunay_edges(model) {
+        draw.line()
+            .weight(1.0)
+            .points(model.points[a].pos, model.points[b].pos)
+            .color(lin_srgba(1.0, 1.0, 1.0, 0.15));
+    }
+
+    if let Some(result) = current_path(model) {
+        let step = if animate { model.path_animation.shown_steps as usize } else { usize::MAX };
+        // points the search never got to are marked usize::MAX, which an unlimited step would count as reached
+        let settled = |i: usize| result.settled_at[i] != usize::MAX && result.settled_at[i] <= step;
+        let discovered = |i: usize| result.discovered_at[i] != usize::MAX && result.discovered_at[i] <= step;
+        let furthest = (0..model.points.len())
+            .filter(|&i| settled(i))
+            .map(|i| result.distance[i])
+            .filter(|d| d.is_finite())
+            .fold(0.0, f32::max)
+            .max(1.0);
+
+        // the search tree so far, coloured by distance from the source
+        for i in (0..model.points.len()).filter(|&i| settled(i)) {
+            let colour = model.palettes.active().sample_scalar(result.distance[i] / furthest);
+            if let Some(parent) = result.parent[i] {
+                draw.line()
+                    .weight(weight / 2.0)
+                    .points(model.points[parent].pos, model.points[i].pos)
+                    .color(colour);
+            }
+            draw_double_circle(draw, model.points[i].pos, colour, 3.0);
+        }
+
+        // points reached but not yet settled
+        for i in (0..model.points.len()).filter(|&i| discovered(i) && !settled(i)) {
+            draw.ellipse()
+                .xy(model.points[i].pos)
+                .radius(5.0)
+                .no_fill()
+                .stroke_weight(1.5)
+                .stroke(WHITE);
+        }
+
+        let target_settled = result.path.last().map_or(false, |&t| settled(t));
+        if target_settled {
+            let path = result.path.iter().map(|&i| model.points[i].pos).collect::<Vec<Vec2>>();
+            draw.polyline()
+                .weight(weight)
+                .join_round()
+                .points(path)
+                .color(WHITE);
         }
     }
-}
\ No newline at end of file
+
+    draw_path_ends(draw, model);
+}
+
+/// Rings round the chosen source and target
+pub(crate) fn draw_path_ends(draw: &Draw, model: &Model) {
+    let ends = [(model.path_ends.source, rgb(0.2, 1.0, 0.4)), (model.path_ends.target, rgb(1.0, 0.3, 0.3))];
+    for (id, colour) in ends {
+        if let Some(point) = id.and_then(|id| model.points.iter().find(|p| p.id == id)) {
+            draw.ellipse()
+                .xy(point.pos)
+                .radius(10.0)
+                .no_fill()
+                .stroke_weight(3.0)
+                .stroke(colour);
+        }
+    }
+}
+
+/// Springs coloured by strain through the palette, squashed at the start and stretched at the end
+pub(crate) fn render_spring_mesh(_app: &App, model: &Model, draw: &Draw) {
+    let index = model.points.iter().enumerate().map(|(i, p)| (p.id, i)).collect::<HashMap<usize, usize>>();
+    let wrapped = 0.5 * model.world.w().min(model.world.h());
+
+    for spring in model.spring_mesh.springs.iter() {
+        let (start, end) = match (index.get(&spring.a), index.get(&spring.b)) {
+            (Some(&a), Some(&b)) => (model.points[a].pos, model.points[b].pos),
+            _ => continue,
+        };
+        let length = start.distance(end);
+        if length > wrapped || spring.rest_length <= f32::EPSILON {
+            continue;
+        }
+
+        let strain = length / spring.rest_length - 1.0;
+        draw.line()
+            .weight(2.0)
+            .points(start, end)
+            .color(model.palettes.active().sample_scalar((0.5 + strain).clamp(0.0, 1.0)));
+    }
+
+    draw_all_points(draw, model);
+}
@@ -1,4 +1,8 @@
 pub mod points;
 pub mod graph;
 pub mod voronoi;
-pub mod perlin;
\ No newline at end of file
+pub mod perlin;
+pub mod stipple;
+pub mod flow;
+pub mod tour;
+pub mod playback;
@@ -1,19 +1,33 @@
 use crate::sketch::*;
+use crate::sketch::noise_field::FlowField;
 
 pub fn render_perlin(app: &App, model: &Model, draw: &Draw) {
-    let boundary = app.window_rect();
+    draw_flow_arrows(app, model, draw);
+    draw_all_points(draw, model);
+}
+
+/// The angle field is coloured by noise value, the curl field by direction
+pub(crate) fn flow_colour(model: &Model, pos: Vec2, flow: Vec2) -> LinSrgb<f32> {
+    match model.settings.flow_field {
+        FlowField::Angle => model.palettes.active().sample(model.noise.value(model.world, pos)),
+        FlowField::Curl => model.palettes.active().sample((flow.angle() / TAU).rem_euclid(1.0)),
+    }
+}
+
+/// A grid of arrows showing the push of the selected flow field
+pub(crate) fn draw_flow_arrows(app: &App, model: &Model, draw: &Draw) {
+    let boundary = model.visible_world(app);
 
     let radius: usize = 10;
 
     for py in ((boundary.y.start as i32 - radius as i32 * 2)..(boundary.y.end as i32 + radius as i32 * 2)).step_by(radius * 2) {
         for px in ((boundary.x.start as i32 - radius as i32 * 2)..(boundary.x.end as i32 + radius as i32 * 2)).step_by(radius * 2) {
             let start = Vec2::new(px as f32, py as f32);
-            let value = tileable_perlin(&model.settings, app, start);
-            let angle = value * TAU;
-            let colour = colour_from_zero_one(value);
+            let flow = model.noise.flow(model.settings.flow_field, model.world, start);
+            let colour = flow_colour(model, start, flow);
 
             let magnitude = radius as f32 * model.settings.perlin_push.value_f32();
-            let end = start + magnitude * Vec2::new(angle.cos(), angle.sin());
+            let end = start + magnitude * flow.clamp_length_max(2.0);
 
             draw.arrow()
                 .start(start)
@@ -22,6 +36,4 @@ pub fn render_perlin(app: &App, model: &Model, draw: &Draw) {
                 .weight(model.settings.perlin_push.value_f32());
         }
     }
-
-    draw_all_points(draw, model);
-}
\ No newline at end of file
+}
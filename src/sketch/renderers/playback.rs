@@ -0,0 +1,144 @@
+use crate::sketch::*;
+use crate::sketch::delaunay::{replay_triangles, TriangulationEvent};
+use crate::sketch::kruskals::Edge;
+use crate::sketch::nearest::BfsStep;
+
+/// Draws the recorded algorithm up to the current step, with that step picked out
+pub(crate) fn render_playback(_app: &App, model: &Model, draw: &Draw) {
+    let playback = &model.playback;
+    let shown = playback.current_step();
+
+    match &playback.recording {
+        None => {}
+        Some((_, RecordedSteps::Kruskal(steps))) => draw_kruskal(model, draw, &steps[..shown]),
+        Some((_, RecordedSteps::Bfs(steps))) => draw_bfs(model, draw, steps, shown),
+        Some((_, RecordedSteps::Triangulation(steps))) => draw_triangulation(model, draw, &playback.points, &steps[..shown]),
+    }
+}
+
+fn draw_kruskal(model: &Model, draw: &Draw, steps: &[(Edge, bool)]) {
+    let points = &model.playback.points;
+    for (edge, accepted) in steps.iter() {
+        let (start, end) = (&points[edge.start], &points[edge.end]);
+        if *accepted {
+            draw.line()
+                .weight(5.0)
+                .points(start.pos, end.pos)
+                .color(average_colours(&[start.colour, end.colour], model.settings.mix_space));
+        } else {
+            draw.line()
+                .weight(1.0)
+                .points(start.pos, end.pos)
+                .color(srgba(1.0, 1.0, 1.0, 0.2));
+        }
+    }
+
+    if let Some((edge, accepted)) = steps.last() {
+        let colour = if *accepted { lin_srgb(0.2, 1.0, 0.3) } else { lin_srgb(1.0, 0.2, 0.2) };
+        draw.line()
+            .weight(8.0)
+            .points(points[edge.start].pos, points[edge.end].pos)
+            .color(colour);
+    }
+
+    for point in points.iter() {
+        draw_double_circle(draw, point.pos, point.colour, 0.5 * model.settings.show_points.value_f32());
+    }
+}
+
+fn draw_bfs(model: &Model, draw: &Draw, steps: &[BfsStep], shown: usize) {
+    // depths are coloured against the whole search so colours don't shift while it plays
+    let max_depth = steps.iter().filter_map(|step| match step {
+        BfsStep::Discover { depth, .. } => Some(*depth),
+        BfsStep::Visit(_) => None,
+    }).max().unwrap_or(0).max(1) as f32;
+    let depth_colour = |depth: usize| model.palettes.active().sample_scalar(depth as f32 / max_depth);
+
+    let mut visited = HashSet::new();
+    let mut frontier = Vec::new();
+    for step in &steps[..shown] {
+        match step {
+            BfsStep::Visit(point) => {
+                visited.insert(point.id);
+                if frontier.is_empty() {
+                    // the root is visited without being discovered
+                    frontier.push((*point, 0));
+                }
+            }
+            BfsStep::Discover { from, to, depth } => {
+                draw.line()
+                    .weight(4.0)
+                    .points(from.pos, to.pos)
+                    .color(depth_colour(*depth));
+                frontier.push((*to, *depth));
+            }
+        }
+    }
+
+    for point in model.playback.points.iter().filter(|p| frontier.iter().all(|(f, _)| f.id != p.id)) {
+        draw_double_circle(draw, point.pos, lin_srgb(0.3, 0.3, 0.3), 0.5 * model.settings.show_points.value_f32());
+    }
+    for (point, depth) in frontier.iter() {
+        if visited.contains(&point.id) {
+            draw_double_circle(draw, point.pos, depth_colour(*depth), model.settings.show_points.value_f32());
+        } else {
+            // discovered and waiting in the queue
+            draw.ellipse()
+                .no_fill()
+                .stroke_weight(2.0)
+                .stroke(depth_colour(*depth))
+                .radius(2.0 * model.settings.show_points.value_f32())
+                .xy(point.pos);
+        }
+    }
+
+    if let Some(BfsStep::Visit(point)) = steps[..shown].iter().rev().find(|step| matches!(step, BfsStep::Visit(_))) {
+        draw.ellipse()
+            .no_fill()
+            .stroke_weight(3.0)
+            .stroke(WHITE)
+            .radius(3.0 * model.settings.show_points.value_f32())
+            .xy(point.pos);
+    }
+}
+
+fn draw_triangulation(model: &Model, draw: &Draw, points: &[Point], steps: &[TriangulationEvent]) {
+    // rebuild the triangles as they were after the last shown step
+    let triangles = replay_triangles(steps);
+    let inserted = steps.iter().filter_map(|step| match step {
+        TriangulationEvent::Insert(i) => Some(*i),
+        _ => None,
+    }).collect::<HashSet<usize>>();
+
+    let corners = |t: usize| [points[triangles[t]].pos, points[triangles[t + 1]].pos, points[triangles[t + 2]].pos];
+    let highlighted = match steps.last() {
+        Some(TriangulationEvent::AddTriangle { t, .. }) => vec![(*t, srgba(0.2, 1.0, 0.3, 0.4))],
+        Some(TriangulationEvent::Flip { a, b, .. }) => vec![(a - a % 3, srgba(1.0, 0.6, 0.1, 0.4)), (b - b % 3, srgba(1.0, 0.6, 0.1, 0.4))],
+        _ => vec![],
+    };
+    for (t, colour) in highlighted {
+        draw.polygon().points(corners(t)).color(colour);
+    }
+
+    for t in (0..triangles.len()).step_by(3) {
+        let [a, b, c] = corners(t);
+        draw.polyline()
+            .weight(1.5)
+            .points([a, b, c, a])
+            .color(srgba(1.0, 1.0, 1.0, 0.7));
+    }
+
+    for (i, point) in points.iter().enumerate() {
+        let colour = if inserted.contains(&i) { point.colour } else { lin_srgb(0.3, 0.3, 0.3) };
+        draw_double_circle(draw, point.pos, colour, 0.5 * model.settings.show_points.value_f32());
+    }
+
+    if let Some(TriangulationEvent::Insert(i)) = steps.last() {
+        draw.ellipse()
+            .no_fill()
+            .stroke_weight(3.0)
+            .stroke(WHITE)
+            .radius(3.0 * model.settings.show_points.value_f32())
+            .xy(points[*i].pos);
+    }
+}
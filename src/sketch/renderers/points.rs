@@ -13,28 +13,7 @@ pub(crate) fn render_point_vectors(_app: &App, model: &Model, draw: &Draw) {
 }
 
 pub(crate) fn render_point_vectors_coloured(app: &App, model: &Model, draw: &Draw) {
-    let boundary = app.window_rect();
-
-    let radius: usize = 10;
-
-    for py in ((boundary.y.start as i32 - radius as i32 * 2)..(boundary.y.end as i32 + radius as i32 * 2)).step_by(radius * 2) {
-        for px in ((boundary.x.start as i32 - radius as i32 * 2)..(boundary.x.end as i32 + radius as i32 * 2)).step_by(radius * 2) {
-            let start = Vec2::new(px as f32, py as f32);
-            let value = tileable_perlin(&model.settings, app, start);
-            let angle = value * TAU;
-            let colour = colour_from_zero_one(value);
-
-
-            let magnitude = radius as f32 * model.settings.perlin_push.value_f32();
-            let end = start + magnitude * Vec2::new(angle.cos(), angle.sin());
-
-            draw.arrow()
-                .start(start)
-                .end(end)
-                .color(colour)
-                .weight(model.settings.perlin_push.value_f32());
-        }
-    }
+    renderers::perlin::draw_flow_arrows(app, model, draw);
 
     draw_all_points(draw, model);
     model.points.iter().for_each(|point| {
@@ -43,27 +22,37 @@ pub(crate) fn render_point_vectors_coloured(app: &App, model: &Model, draw: &Dra
         draw.arrow()
             .points(point.pos, point.pos + mov_vec * 50.0)
             .weight(2.0)
-            .color(colour_from_zero_one(mov_vec.angle() / TAU));
+            .color(model.palettes.active().sample((mov_vec.angle() / TAU).rem_euclid(1.0)));
     });
 }
 
 
-pub(crate) fn render_speed_sizing(app: &App, model: &Model, draw: &Draw) {
-    for point in model.points.iter() {
-        let pos = point.pos;
-        let movement = point.pos - point.last_pos;
-        let movement_length = movement.length();
-        let max_length = app.window_rect().wh().max_element();
-        let mut dist = if movement_length > max_length / 2.0 {
-            0.0
-        } else {
-            movement_length * 5.0 * model.settings.show_points.value_f32()
-        };
+pub(crate) fn render_speed_sizing(_app: &App, model: &Model, draw: &Draw) {
+    // coloured relative to the fastest point this frame
+    let max_size = model.points.iter()
+        .map(|p| speed_size(model, p))
+        .fold(2.0, f32::max);
 
-        if dist < 2.0 {
-            dist = 2.0
-        }
+    for point in model.points.iter() {
+        let dist = speed_size(model, point);
+        let colour = model.palettes.active().sample_scalar(dist / max_size);
+        draw_double_circle(draw, point.pos, colour, dist);
+    }
+}
 
-        draw_double_circle(draw, pos, point.colour, dist);
+fn speed_size(model: &Model, point: &Point) -> f32 {
+    let movement = point.pos - point.last_pos;
+    let movement_length = movement.length();
+    let max_length = model.world.wh().max_element();
+    let mut dist = if movement_length > max_length / 2.0 {
+        0.0
+    } else {
+        movement_length * 5.0 * model.settings.show_points.value_f32()
+    };
+
+    if dist < 2.0 {
+        dist = 2.0
     }
-}
\ No newline at end of file
+
+    dist
+}
@@ -0,0 +1,18 @@
+use crate::sketch::*;
+use crate::sketch::stipple::stipple_radius;
+
+pub(crate) fn render_stipple(_app: &App, model: &Model, draw: &Draw) {
+    let rect = model.world;
+
+    draw.rect()
+        .xy(rect.xy())
+        .wh(rect.wh())
+        .color(WHITE);
+
+    for point in model.points.iter() {
+        draw.ellipse()
+            .xy(point.pos)
+            .radius(stipple_radius(&model.settings, &model.image, rect, point.pos))
+            .color(BLACK);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::sketch::*;
+
+/// The travelling salesman tour as a single closed line
+pub(crate) fn render_tour(_app: &App, model: &Model, draw: &Draw) {
+    let settings = model.settings.for_mode(TOUR_RENDER_MODE);
+    let weight = &settings[1];
+
+    let outline = tour_outline(&model.settings, &model.tour, &model.points);
+    if outline.len() > 2 {
+        let last = (outline.len() - 1) as f32;
+        let points = outline.into_iter().enumerate().map(|(i, pos)| {
+            // a cyclic palette meets itself where the tour closes
+            let c = if weight.bool { model.palettes.active().sample(i as f32 / last) } else { lin_srgb(1.0, 1.0, 1.0) };
+            (pos, lin_srgba(c.red, c.green, c.blue, 1.0))
+        }).collect::<Vec<(Vec2, LinSrgba<f32>)>>();
+
+        draw.polyline()
+            .weight(weight.value_f32())
+            .join_round()
+            .points_colored(points);
+    }
+
+    draw_all_points(draw, model);
+}
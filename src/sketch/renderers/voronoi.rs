@@ -1,39 +1,122 @@
 use crate::sketch::*;
 use crate::sketch::delaunay::{delaunay_triangulation, voronoi_diagram};
 
+pub(crate) const DELAUNAY_RENDER_MODE: u8 = 2;
+
 pub(crate) fn render_delaunay(app: &App, model: &Model, draw: &Draw) {
+    let settings = model.settings.for_mode(DELAUNAY_RENDER_MODE);
+    let interpolate = settings[0].bool;
+    let shading = settings[1].bool;
+    let light = light_direction(settings[1].value_f32());
+    let height_scale = settings[2].value_f32();
+    let ambient = settings[3].value_f32();
+
+    // the corners added to cover the world aren't points, so they don't get a say in the colour
+    let ids = model.points.iter().map(|p| p.id).collect::<HashSet<usize>>();
+    let weight = |p: &Point| if ids.contains(&p.id) { 1.0 } else { 0.0 };
+
+    let mut mesh = vec![];
+
     for (p0, p1, p2) in delaunay_triangulation(app, model, true) {
-        let colours = vec![p0.colour, p1.colour, p2.colour];
-        let colour = average_lin_srgb(&colours);
+        let colours = [(p0.colour, weight(&p0)), (p1.colour, weight(&p1)), (p2.colour, weight(&p2))];
+        let colour = mix_colours(&colours, model.settings.mix_space);
+
+        let shade = if shading {
+            face_shade(model, [p0.pos, p1.pos, p2.pos], light, height_scale, ambient)
+        } else {
+            1.0
+        };
+        let shaded = |c: LinSrgb<f32>| lin_srgba(c.red * shade, c.green * shade, c.blue * shade, 1.0);
+
+        if interpolate {
+            for p in [p0, p1, p2] {
+                // world corners take the face colour rather than bleeding black in
+                let c = if weight(&p) > 0.0 { p.colour } else { colour };
+                mesh.push((p.pos, shaded(c)));
+            }
+        } else {
+            draw.polygon()
+                .points([Vec2::from(p0), Vec2::from(p1), Vec2::from(p2), Vec2::from(p0)])
+                .color(shaded(colour));
+        }
+    }
 
-        draw.polygon()
-            .points([Vec2::from(p0), Vec2::from(p1), Vec2::from(p2), Vec2::from(p0)])
-            .color(colour);
+    if !mesh.is_empty() {
+        // every three points is a triangle, and the vertex colours are blended across it
+        draw.mesh().points_colored(mesh);
     }
 
     draw_all_points(draw, model);
 }
 
+/// Light coming in from `angle` degrees round the screen and 45 degrees up from it
+fn light_direction(angle: f32) -> Vec3 {
+    let angle = angle.to_radians();
+    Vec3::new(angle.cos(), angle.sin(), 1.0).normalize()
+}
+
+/// Lambertian brightness of the triangle once its corners are lifted onto the noise as a height field
+fn face_shade(model: &Model, corners: [Vec2; 3], light: Vec3, height_scale: f32, ambient: f32) -> f32 {
+    let lifted = corners.map(|p| p.extend(model.noise.value(model.world, p) * height_scale));
+    let normal = (lifted[1] - lifted[0]).cross(lifted[2] - lifted[0]).normalize_or_zero();
+    // triangles can be wound either way round, the face always points at the viewer
+    let normal = if normal.z < 0.0 { -normal } else { normal };
+
+    ambient + (1.0 - ambient) * normal.dot(light).max(0.0)
+}
+
+pub(crate) const VORONOI_RENDER_MODE: u8 = 1;
+
 pub(crate) fn render_voronoi(app: &App, model: &Model, draw: &Draw) {
-    let voronoi = voronoi_diagram(app, model);
-
-    for (points, colour) in voronoi.clone().into_iter() {
-        draw.polygon()
-            .points(points)
-            .color(colour);
-    };
-    let cell_settings = &model.settings.settings_per_render_mode[1].as_ref().unwrap()[0];
-    if cell_settings.bool {
-        // border lines
-        for polyline in voronoi.into_iter() {
-            let mut points = polyline.0;
-            if points.len() == 0 {
-                continue;
+    let settings = model.settings.for_mode(VORONOI_RENDER_MODE);
+    let (border, gap, corners, smoothing, lead, gradient) = (&settings[0], &settings[1], &settings[2], &settings[3], &settings[4], &settings[5]);
+
+    let cells = voronoi_diagram(app, model).into_iter()
+        .enumerate()
+        .map(|(i, (points, _))| (style_cell(&points, gap, corners, smoothing), automaton_colour(model, &model.points[i])))
+        .filter(|(points, _)| points.len() >= 3)
+        .collect::<Vec<(Vec<Vec2>, LinSrgb<f32>)>>();
+
+    let mut mesh = vec![];
+    for (points, colour) in cells.iter() {
+        match centroid(points) {
+            Some(centre) if gradient.bool => {
+                let white = lin_srgb(1.0, 1.0, 1.0);
+                let highlight = mix_colours(&[(*colour, 1.0 - gradient.value_f32()), (white, gradient.value_f32())], model.settings.mix_space);
+                let centre_colour = lin_srgba(highlight.red, highlight.green, highlight.blue, 1.0);
+                let edge_colour = lin_srgba(colour.red, colour.green, colour.blue, 1.0);
+                // a fan from the centroid, lighter in the middle
+                for (i, &a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    mesh.extend([(centre, centre_colour), (a, edge_colour), (b, edge_colour)]);
+                }
             }
-            points.push(points[0]);
+            _ => {
+                draw.polygon()
+                    .points(points.clone())
+                    .color(*colour);
+            }
+        }
+    }
+    if !mesh.is_empty() {
+        draw.mesh().points_colored(mesh);
+    }
+
+    let closed = |points: &Vec<Vec2>| points.iter().chain(points.first()).copied().collect::<Vec<Vec2>>();
+    if lead.bool {
+        for (points, _) in cells.iter() {
+            draw.polyline()
+                .weight(lead.value_f32())
+                .join_round()
+                .points(closed(points))
+                .color(lin_srgb(0.015, 0.015, 0.02));
+        }
+    }
+    if border.bool {
+        for (points, _) in cells.iter() {
             draw.polyline()
-                .weight(cell_settings.value_f32())
-                .points(points)
+                .weight(border.value_f32())
+                .points(closed(points))
                 .color(BLACK);
         }
     }
@@ -41,25 +124,27 @@ pub(crate) fn render_voronoi(app: &App, model: &Model, draw: &Draw) {
     draw_all_points(draw, model);
 }
 
-pub(crate) fn render_bubbles(app: &App, model: &Model, draw: &Draw) {
-    let voronoi = voronoi_diagram(app, model);
-    voronoi.into_iter().enumerate().for_each(|(_i, (points, c))|{
-        match centroid(&points) {
-            None => {}
-            Some(centroid) => {
-                let radius = points
-                    .windows(2)
-                    .map(|edge| distance_to_line(centroid, edge[0], edge[1]))
-                    .fold(f32::MAX, f32::min);
-
-                draw.ellipse()
-                    .xy(centroid)
-                    .color(c)
-                    .radius(radius);
+/// Applies the inset, corner rounding and smoothing that are switched on, in that order
+fn style_cell(points: &Vec<Vec2>, gap: &SettingsItem, corners: &SettingsItem, smoothing: &SettingsItem) -> Vec<Vec2> {
+    let mut cell = anticlockwise(points);
+    if gap.bool {
+        cell = inset_polygon(&cell, gap.value_f32() / 2.0);
+    }
+    if corners.bool && cell.len() >= 3 {
+        cell = round_polygon(&cell, corners.value_f32());
+    }
+    if smoothing.bool {
+        cell = chaikin(&cell, smoothing.value_u32() as usize);
+    }
+    cell
+}
 
-            }
-        }
-    });
+pub(crate) fn render_bubbles(_app: &App, model: &Model, draw: &Draw) {
+    for bubble in model.bubbles.bubbles.iter() {
+        draw.ellipse()
+            .xy(bubble.centre)
+            .color(bubble.colour)
+            .radius(bubble.radius);
+    }
     draw_all_points(draw, model);
 }
-
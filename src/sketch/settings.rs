@@ -1,4 +1,17 @@
 use crate::sketch::*;
+use crate::sketch::density::{CentroidDensity, DensitySampling};
+use crate::sketch::tool::{PaintColour, Tool};
+use crate::sketch::camera::ResizeMode;
+use crate::sketch::noise_field::{FlowField, NoiseSettings};
+use crate::sketch::reaction_diffusion::ReactionSettings;
+use crate::sketch::cellular_automaton::AutomatonSettings;
+use crate::sketch::colour_by::ColourBy;
+use crate::sketch::colour_mix::MixSpace;
+use crate::sketch::proximity::ProximityGraph;
+use crate::sketch::tsp::TourConstruction;
+use crate::sketch::paths::PathSearch;
+use crate::sketch::playback::PlaybackAlgorithm;
+use crate::sketch::kruskals::MstAlgorithm;
 
 #[derive(Clone, Debug)]
 pub(crate) struct Settings {
@@ -7,42 +20,556 @@ pub(crate) struct Settings {
     pub show_points: SettingsItem,
     pub mouse_push: SettingsItem,
     pub perlin_push: SettingsItem,
+    pub flow_field: FlowField,
     pub centroid_push: SettingsItem,
     pub simulation_speed: SettingsItem,
     pub timer_pull: SettingsItem,
-    pub perlin_seed: SettingsItem,
+    pub noise: NoiseSettings,
+    pub reaction: ReactionSettings,
+    pub automaton: AutomatonSettings,
+    pub centroid_density: CentroidDensity,
+    pub centroid_sampling: DensitySampling,
+    pub centroid_resolution: SettingsItem,
+    pub density_falloff: SettingsItem,
+    pub tool: Tool,
+    pub brush_radius: SettingsItem,
+    pub paint_spacing: SettingsItem,
+    pub paint_source: PaintColour,
+    pub paint_colour: [f32; 3],
+    pub pin_dragged: bool,
+    pub touch_push: SettingsItem,
+    pub touch_gestures: bool,
+    pub world_scale: SettingsItem,
+    pub resize_mode: ResizeMode,
+    pub palette_colours: SettingsItem,
+    pub colour_by: ColourBy,
+    pub colour_range_smoothing: SettingsItem,
+    pub mix_space: MixSpace,
+    pub proximity_graph: ProximityGraph,
+    pub tour_construction: TourConstruction,
+    pub path_search: PathSearch,
+    pub playback_algorithm: PlaybackAlgorithm,
+    pub mst_algorithm: MstAlgorithm,
 }
 
 impl Settings {
     pub(crate) fn get_setting_for_render_mode(mode: u8) -> Option<Vec<SettingsItem>> {
         match mode {
-            0 => {
-                Some(vec![SettingsItem {
-                    slider_label: "Radial Distance Scaling Factor".to_string(),
-                    slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
-                        value: 0.5,
-                        range_min: 0.0,
-                        range_max: 1.0,
-                    }),
-                    show_slider: true,
-                    bool_label: "".to_string(),
-                    bool: false,
-                    show_bool: false,
-                }])
-            }
             1 => {
-                Some(vec![SettingsItem{
-                    slider_label: "Cell border weight".to_string(),
-                    slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
-                        value: 1.25,
-                        range_min: 0.0,
-                        range_max: 4.0,
-                    }),
-                    show_slider: true,
-                    bool_label: "Show cell border?".to_string(),
-                    bool: true,
-                    show_bool: true,
-                }])
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Cell border weight".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 1.25,
+                            range_min: 0.0,
+                            range_max: 4.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Show cell border?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Cell gap".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 4.0,
+                            range_min: 0.0,
+                            range_max: 20.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Inset cells?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Corner radius".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 8.0,
+                            range_min: 0.0,
+                            range_max: 40.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Round corners?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Smoothing passes".to_string(),
+                        slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                            value: 2,
+                            range_min: 1,
+                            range_max: 6,
+                        }),
+                        show_slider: true,
+                        bool_label: "Chaikin smoothing?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Lead line weight".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 5.0,
+                            range_min: 0.5,
+                            range_max: 16.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Stained glass lead lines?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Centre highlight".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.5,
+                            range_min: 0.0,
+                            range_max: 1.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Gradient towards centroid?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                ])
+            }
+            2 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.0,
+                            range_min: 0.0,
+                            range_max: 1.0,
+                        }),
+                        show_slider: false,
+                        bool_label: "Interpolate vertex colours?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Light angle".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 135.0,
+                            range_min: 0.0,
+                            range_max: 360.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Flat shade from noise heights?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Height scale".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 120.0,
+                            range_min: 0.0,
+                            range_max: 400.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Ambient light".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.35,
+                            range_min: 0.0,
+                            range_max: 1.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                ])
+            }
+            3 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Fade time".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.5,
+                            range_min: 0.0,
+                            range_max: 3.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Stabilise tree?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Switch margin".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.05,
+                            range_min: 0.0,
+                            range_max: 0.5,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Spring stiffness".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.05,
+                            range_min: 0.0,
+                            range_max: 0.5,
+                        }),
+                        show_slider: true,
+                        bool_label: "Spring forces?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                ])
+            }
+            7 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Circles per cell".to_string(),
+                        slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                            value: 1,
+                            range_min: 1,
+                            range_max: 8,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Smallest extra circle".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 3.0,
+                            range_min: 0.5,
+                            range_max: 20.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Growth speed".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 4.0,
+                            range_min: 0.5,
+                            range_max: 20.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Animate growth?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Circle precision".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.5,
+                            range_min: 0.1,
+                            range_max: 5.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                ])
+            }
+            8 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Stipple dot size".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 2.5,
+                            range_min: 0.5,
+                            range_max: 8.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Size dots by darkness?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Darkness contrast".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 1.0,
+                            range_min: 0.25,
+                            range_max: 4.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Weight centroids by darkness?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Stipple count".to_string(),
+                        slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                            value: 4000,
+                            range_min: 500,
+                            range_max: 20000,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                ])
+            }
+            10 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Trail length".to_string(),
+                        slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                            value: 32,
+                            range_min: 2,
+                            range_max: 64,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Trail fade".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 1.0,
+                            range_min: 0.0,
+                            range_max: 1.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Fade trails?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Trail width".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 2.0,
+                            range_min: 0.5,
+                            range_max: 8.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                ])
+            }
+            11 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Streamline spacing".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 20.0,
+                            range_min: 5.0,
+                            range_max: 80.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Streamline length (samples)".to_string(),
+                        slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                            value: 300,
+                            range_min: 10,
+                            range_max: 1000,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Streamline fade".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 1.0,
+                            range_min: 0.0,
+                            range_max: 1.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Fade streamline ends?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Streamline width".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 2.0,
+                            range_min: 0.5,
+                            range_max: 8.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                ])
+            }
+            12 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Neighbours (k nearest)".to_string(),
+                        slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                            value: 3,
+                            range_min: 1,
+                            range_max: 12,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Beta (beta skeleton)".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 1.5,
+                            range_min: 1.0,
+                            range_max: 3.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Edge weight".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 2.0,
+                            range_min: 0.5,
+                            range_max: 8.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Colour edges by length?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                ])
+            }
+            13 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Improvement time (ms per frame)".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 4.0,
+                            range_min: 0.5,
+                            range_max: 30.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Improve tour?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Line weight".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 2.0,
+                            range_min: 0.5,
+                            range_max: 8.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Colour along the tour?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Smoothing passes".to_string(),
+                        slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                            value: 2,
+                            range_min: 1,
+                            range_max: 6,
+                        }),
+                        show_slider: true,
+                        bool_label: "Smooth tour?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                ])
+            }
+            15 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Frontier steps per second".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 60.0,
+                            range_min: 1.0,
+                            range_max: 500.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Animate frontier?".to_string(),
+                        bool: true,
+                        show_bool: true,
+                    },
+                    SettingsItem {
+                        slider_label: "Path weight".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 4.0,
+                            range_min: 1.0,
+                            range_max: 10.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                ])
+            }
+            16 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Steps per second".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 10.0,
+                            range_min: 0.5,
+                            range_max: 200.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                ])
+            }
+            17 => {
+                Some(vec![
+                    SettingsItem {
+                        slider_label: "Stiffness".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.1,
+                            range_min: 0.0,
+                            range_max: 0.5,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Damping".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 0.2,
+                            range_min: 0.0,
+                            range_max: 1.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "".to_string(),
+                        bool: false,
+                        show_bool: false,
+                    },
+                    SettingsItem {
+                        slider_label: "Tear at stretch".to_string(),
+                        slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                            value: 2.5,
+                            range_min: 1.2,
+                            range_max: 5.0,
+                        }),
+                        show_slider: true,
+                        bool_label: "Tearing?".to_string(),
+                        bool: false,
+                        show_bool: true,
+                    },
+                ])
             }
             // 7 => {
             //     Some(vec![SettingsItem {
@@ -61,6 +588,11 @@ impl Settings {
             _ => {None}
         }
     }
+
+    /// The sliders of a render mode, empty for the modes without any
+    pub(crate) fn for_mode(&self, mode: u8) -> &[SettingsItem] {
+        self.settings_per_render_mode[mode as usize].as_deref().unwrap_or(&[])
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -106,6 +638,31 @@ impl SettingsItem {
             SettingsSlider::SettingsSliderU32(x) => {x.value}
         }
     }
+
+    /// Sets the value, clamped to the range of the slider
+    pub(crate) fn set_value_f32(&mut self, value: f32) {
+        match &mut self.slider {
+            SettingsSlider::SettingSliderF32(x) => {x.value = value.clamp(x.range_min, x.range_max)}
+            SettingsSlider::SettingsSliderU32(_) => {panic!("Wrong slider")}
+        }
+    }
+}
+
+/// A setting that is picked from a fixed list of options
+pub(crate) trait SettingsChoice: Copy + PartialEq + 'static {
+    const ALL: &'static [Self];
+
+    fn name(&self) -> &'static str;
+}
+
+pub(crate) fn show_choice<T: SettingsChoice>(ui: &mut Ui, label: &str, value: &mut T) {
+    egui::ComboBox::from_label(label)
+        .selected_text(value.name())
+        .show_ui(ui, |ui| {
+            for choice in T::ALL {
+                ui.selectable_value(value, *choice, choice.name());
+            }
+        });
 }
 
 #[derive(Clone, Debug)]
@@ -170,6 +727,7 @@ impl Default for Settings {
                 bool: true,
                 show_bool: true,
             },
+            flow_field: FlowField::Angle,
             centroid_push: SettingsItem {
                 slider_label: "Centroid push strength".to_string(),
                 slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
@@ -206,12 +764,114 @@ impl Default for Settings {
                 bool: true,
                 show_bool: true,
             },
-            perlin_seed: SettingsItem {
-                slider_label: "Perlin seed".to_string(),
+            noise: NoiseSettings::default(),
+            reaction: ReactionSettings::default(),
+            automaton: AutomatonSettings::default(),
+            centroid_density: CentroidDensity::Uniform,
+            centroid_sampling: DensitySampling::TriangleFan,
+            centroid_resolution: SettingsItem {
+                slider_label: "Density samples per cell".to_string(),
                 slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
-                    value: random_range(0,65535),
-                    range_min: 0,
-                    range_max: 65535,
+                    value: 4,
+                    range_min: 1,
+                    range_max: 16,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            density_falloff: SettingsItem {
+                slider_label: "Mouse density falloff radius".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 200.0,
+                    range_min: 20.0,
+                    range_max: 800.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            tool: Tool::Off,
+            brush_radius: SettingsItem {
+                slider_label: "Brush radius".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 40.0,
+                    range_min: 5.0,
+                    range_max: 300.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            paint_spacing: SettingsItem {
+                slider_label: "Paint spacing".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 15.0,
+                    range_min: 2.0,
+                    range_max: 100.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            paint_source: PaintColour::Picked,
+            paint_colour: [1.0, 0.4, 0.1],
+            pin_dragged: true,
+            touch_push: SettingsItem {
+                slider_label: "Touch push strength".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 1.0,
+                    range_min: -5.0,
+                    range_max: 5.0,
+                }),
+                show_slider: true,
+                bool_label: "Touch push?".to_string(),
+                bool: true,
+                show_bool: true,
+            },
+            touch_gestures: true,
+            world_scale: SettingsItem {
+                slider_label: "World size (windows across)".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 1.0,
+                    range_min: 1.0,
+                    range_max: 4.0,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            resize_mode: ResizeMode::Rescale,
+            palette_colours: SettingsItem {
+                slider_label: "Colours to extract".to_string(),
+                slider: SettingsSlider::SettingsSliderU32(SettingSliderU32{
+                    value: 6,
+                    range_min: 2,
+                    range_max: 16,
+                }),
+                show_slider: true,
+                bool_label: "".to_string(),
+                bool: false,
+                show_bool: false,
+            },
+            colour_by: ColourBy::Original,
+            mix_space: MixSpace::Oklab,
+            proximity_graph: ProximityGraph::Gabriel,
+            tour_construction: TourConstruction::Greedy,
+            path_search: PathSearch::AStar,
+            playback_algorithm: PlaybackAlgorithm::Kruskal,
+            mst_algorithm: MstAlgorithm::Kruskal,
+            colour_range_smoothing: SettingsItem {
+                slider_label: "Colour range smoothing".to_string(),
+                slider: SettingsSlider::SettingSliderF32(SettingSliderF32{
+                    value: 0.9,
+                    range_min: 0.0,
+                    range_max: 0.99,
                 }),
                 show_slider: true,
                 bool_label: "".to_string(),
@@ -66,7 +66,7 @@ fn render_3d(app: &App, model: &Model, draw: &Draw) {
 fn render_2d(app: &App, model: &Model, draw: &Draw) {
     let size = app.window_rect().wh();
     let max_radius = 350.0;
-    let t = model.settings.settings_per_render_mode[7].as_ref().unwrap()[0].value_f32().clamp(0.0,1.0);
+    let t = model.settings.for_mode(7)[0].value_f32().clamp(0.0,1.0);
 
     // if model.settings.show_points.bool {
     model.points.iter().for_each(|point| {
@@ -120,30 +120,6 @@ pub(crate) fn dfs(edges: Vec<(Point, Point)>) -> (Vec<(Point, Point)>, Vec<(Poin
 
     (tree_edges, distances)
 }
-pub(crate) fn nearest_neighbour(vec: &Vec<Point>) -> Vec<(usize, usize)> {
-    let mut input = vec.iter().map(|x| x.pos).collect::<Vec<Vec2>>();
-    let mut walk = vec![];
-
-    let mut i: usize = 0;
-    loop {
-        let current_elem = input[i];
-        input.remove(i);
-        let next = match find_nearest(current_elem, &input) {
-            None => { break; }
-            Some(x) => { x }
-        };
-        walk.push((i, next));
-        i = next;
-    }
-    walk.push((i, 0));
-    walk
-}
-fn find_nearest(current: Vec2, others: &Vec<Vec2>) -> Option<usize> {
-    let smallest = others.iter().min_by(|&&x, &&y| {
-        current.distance(x).partial_cmp(&current.distance(y)).unwrap()
-    })?;
-    others.iter().position(|x| x == smallest)
-}
 impl Model {
     fn count_non_finite_points(&self) -> usize {
         self.points.iter().filter(|x| { x.is_wrong() }).count()
@@ -0,0 +1,102 @@
+use crate::sketch::*;
+use crate::sketch::delaunay::delaunay_edges;
+
+pub(crate) const SPRING_MESH_RENDER_MODE: u8 = 17;
+
+/// A spring between two points, by id, at the length the edge had when the mesh was built
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Spring {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+}
+
+/// Every Delaunay edge as a damped spring, so the points hang together like cloth or jelly.
+/// The points keep their momentum in here, as they otherwise only move by the forces of the current frame.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SpringMesh {
+    pub springs: Vec<Spring>,
+    /// Ids of the points the mesh was built for, it is built again when these change
+    ids: Vec<usize>,
+    velocity: Vec<Vec2>,
+}
+
+impl SpringMesh {
+    fn new(model: &Model) -> SpringMesh {
+        let springs = delaunay_edges(model).into_iter().map(|(a, b)| Spring {
+            a: model.points[a].id,
+            b: model.points[b].id,
+            rest_length: model.points[a].pos.distance(model.points[b].pos),
+        }).collect();
+
+        SpringMesh {
+            springs,
+            ids: model.points.iter().map(|p| p.id).collect(),
+            velocity: vec![Vec2::ZERO; model.points.len()],
+        }
+    }
+}
+
+/// Hooke's law with damping along every spring, tearing the ones stretched too far.
+/// `dt` is the time step in frames, which the simulation speed stretches further.
+pub(crate) fn apply_spring_mesh(model: &mut Model, dt: f32) {
+    if model.settings.render_mode != SPRING_MESH_RENDER_MODE {
+        model.spring_mesh = SpringMesh::default();
+        return;
+    }
+    if model.spring_mesh.ids.len() != model.points.len() || model.spring_mesh.ids.iter().zip(model.points.iter()).any(|(id, p)| *id != p.id) {
+        model.spring_mesh = SpringMesh::new(model);
+    }
+
+    let settings = model.settings.for_mode(SPRING_MESH_RENDER_MODE);
+    let speed = model.settings.simulation_speed.value_f32();
+    let dt = dt * speed;
+    let tear_at = if settings[2].bool { settings[2].value_f32() } else { f32::INFINITY };
+    // an edge longer than half the world has been wrapped around it, not stretched
+    let wrapped = 0.5 * model.world.w().min(model.world.h());
+
+    let SpringMesh { springs, ids, velocity } = &mut model.spring_mesh;
+    let index = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect::<HashMap<usize, usize>>();
+    let mut force = vec![Vec2::ZERO; model.points.len()];
+
+    // explicit steps blow up once a point's springs together are too stiff for the step,
+    // so long frames and high speeds soften the springs rather than shake the mesh apart
+    let mut degree = vec![0; model.points.len()];
+    for spring in springs.iter() {
+        degree[index[&spring.a]] += 1;
+        degree[index[&spring.b]] += 1;
+    }
+    let max_degree = degree.into_iter().max().unwrap_or(0).max(1) as f32;
+    let stiffness = settings[0].value_f32().min(3.6 / (max_degree * dt * dt));
+    let damping = settings[1].value_f32().min(1.8 / (max_degree * dt));
+
+    springs.retain(|spring| {
+        let (a, b) = (index[&spring.a], index[&spring.b]);
+        let offset = model.points[b].pos - model.points[a].pos;
+        let length = offset.length();
+        if length <= f32::EPSILON || length > wrapped {
+            return true;
+        }
+        if length > spring.rest_length * tear_at {
+            return false;
+        }
+
+        let direction = offset / length;
+        let closing_speed = (velocity[b] - velocity[a]).dot(direction);
+        let f = direction * (stiffness * (length - spring.rest_length) + damping * closing_speed);
+        force[a] += f;
+        force[b] -= f;
+        true
+    });
+
+    for (i, point) in model.points.iter_mut().enumerate() {
+        if point.pinned {
+            velocity[i] = Vec2::ZERO;
+            continue;
+        }
+        // a little drag so the whole mesh settles once it is left alone
+        velocity[i] = (velocity[i] + force[i] * dt) * (1.0 - 0.1 * damping).powf(dt);
+        // moved by moving_vec * dt after this, so the simulation speed goes in here
+        point.moving_vec += velocity[i] * speed;
+    }
+}
@@ -0,0 +1,132 @@
+use crate::sketch::*;
+use crate::sketch::delaunay::delaunay_edges;
+use crate::sketch::kruskals::{minimum_spanning_tree_weighted, weighted_edges};
+
+pub(crate) const MST_RENDER_MODE: u8 = 3;
+
+/// An edge that is in the spanning tree, or was recently and is fading out
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct TreeEdge {
+    /// How far faded in, from 0 to 1
+    pub presence: f32,
+    pub in_tree: bool,
+    /// The length the edge had when it joined the tree, which the spring pulls back towards
+    pub rest_length: f32,
+}
+
+/// The spanning tree kept from frame to frame, by point id so the edges survive points being added and removed.
+/// An edge already in the tree is only swapped out when a new one is shorter by more than the switch margin,
+/// so near ties don't flicker back and forth as the points move.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StableTree {
+    pub edges: HashMap<(usize, usize), TreeEdge>,
+    /// How fast the tree springs are moving each point, by id
+    velocity: HashMap<usize, Vec2>,
+}
+
+fn edge_key(points: &[Point], a: usize, b: usize) -> (usize, usize) {
+    let (a, b) = (points[a].id, points[b].id);
+    (a.min(b), a.max(b))
+}
+
+pub(crate) fn update_stable_tree(model: &mut Model, dt: f32) {
+    let settings = model.settings.for_mode(MST_RENDER_MODE);
+    if model.settings.render_mode != MST_RENDER_MODE || !settings[0].bool {
+        model.stable_tree = StableTree::default();
+        return;
+    }
+    let fade_time = settings[0].value_f32();
+    let margin = settings[1].value_f32();
+
+    let points = model.points.iter().map(|p| p.pos).collect::<Vec<Vec2>>();
+    let mut edges = weighted_edges(&points, &delaunay_edges(model));
+    for edge in edges.iter_mut() {
+        let key = edge_key(&model.points, edge.start, edge.end);
+        if model.stable_tree.edges.get(&key).map_or(false, |e| e.in_tree) {
+            edge.weight *= 1.0 - margin;
+        }
+    }
+
+    let tree = minimum_spanning_tree_weighted(points.len(), edges, model.settings.mst_algorithm);
+
+    let tree_edges = &mut model.stable_tree.edges;
+    tree_edges.values_mut().for_each(|e| e.in_tree = false);
+    for edge in tree {
+        let key = edge_key(&model.points, edge.start, edge.end);
+        let length = points[edge.start].distance(points[edge.end]);
+        // an edge coming back before it has faded out keeps its old rest length
+        tree_edges.entry(key).or_insert(TreeEdge { presence: 0.0, in_tree: true, rest_length: length }).in_tree = true;
+    }
+
+    let step = if fade_time > 0.0 { dt / fade_time } else { 1.0 };
+    for edge in tree_edges.values_mut() {
+        edge.presence = if edge.in_tree { (edge.presence + step).min(1.0) } else { edge.presence - step };
+    }
+    tree_edges.retain(|_, e| e.in_tree || e.presence > 0.0);
+}
+
+/// Damping of the tree springs, so a pulled tree settles instead of ringing
+const TREE_DAMPING: f32 = 0.2;
+
+/// Pulls the ends of every tree edge towards its rest length, so the tree holds its shape like a physical structure.
+/// Integrated like the spring mesh, `dt` is the time step in frames, which the simulation speed stretches further.
+pub(crate) fn apply_tree_springs(model: &mut Model, dt: f32) {
+    let settings = model.settings.for_mode(MST_RENDER_MODE);
+    if model.settings.render_mode != MST_RENDER_MODE || !settings[0].bool || !settings[2].bool {
+        model.stable_tree.velocity.clear();
+        return;
+    }
+    let speed = model.settings.simulation_speed.value_f32();
+    let dt = dt * speed;
+    // an edge longer than half the world has been wrapped around it, not stretched
+    let wrapped = 0.5 * model.world.w().min(model.world.h());
+
+    let index = model.points.iter().enumerate().map(|(i, p)| (p.id, i)).collect::<HashMap<usize, usize>>();
+    let StableTree { edges, velocity } = &mut model.stable_tree;
+    let springs = edges.iter()
+        .filter(|(_, e)| e.in_tree)
+        .filter_map(|(&(a, b), e)| match (index.get(&a), index.get(&b)) {
+            (Some(&a), Some(&b)) => Some((a, b, e.rest_length)),
+            _ => None,
+        })
+        .collect::<Vec<(usize, usize, f32)>>();
+
+    // the same limits as the spring mesh, so long frames soften the springs rather than shake the tree apart
+    let mut degree = vec![0; model.points.len()];
+    for &(a, b, _) in springs.iter() {
+        degree[a] += 1;
+        degree[b] += 1;
+    }
+    let max_degree = degree.into_iter().max().unwrap_or(0).max(1) as f32;
+    let stiffness = settings[2].value_f32().min(3.6 / (max_degree * dt * dt));
+    let damping = TREE_DAMPING.min(1.8 / (max_degree * dt));
+
+    let velocity_of = |velocity: &HashMap<usize, Vec2>, i: usize| velocity.get(&model.points[i].id).copied().unwrap_or(Vec2::ZERO);
+    let mut force = vec![Vec2::ZERO; model.points.len()];
+    for (a, b, rest_length) in springs {
+        let offset = model.points[b].pos - model.points[a].pos;
+        let length = offset.length();
+        if length <= f32::EPSILON || length > wrapped {
+            continue;
+        }
+
+        // Hooke's law with damping along the edge
+        let direction = offset / length;
+        let closing_speed = (velocity_of(velocity, b) - velocity_of(velocity, a)).dot(direction);
+        let f = direction * (stiffness * (length - rest_length) + damping * closing_speed);
+        force[a] += f;
+        force[b] -= f;
+    }
+
+    velocity.retain(|id, _| index.contains_key(id));
+    for (i, point) in model.points.iter_mut().enumerate() {
+        if point.pinned {
+            velocity.remove(&point.id);
+            continue;
+        }
+        let v = velocity.entry(point.id).or_insert(Vec2::ZERO);
+        *v = (*v + force[i] * dt) * (1.0 - 0.1 * damping).powf(dt);
+        // moved by moving_vec * dt after this, so the simulation speed goes in here
+        point.moving_vec += *v * speed;
+    }
+}
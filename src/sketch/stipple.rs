@@ -0,0 +1,50 @@
+use std::fmt::Write;
+use crate::sketch::*;
+use crate::sketch::image_field::ImageField;
+
+pub(crate) const STIPPLE_RENDER_MODE: u8 = 8;
+
+/// Darkness of the image at `pos` after the contrast curve, used both to weight centroids and size dots
+pub(crate) fn stipple_darkness(settings: &Settings, image: &ImageField, rect: Rect, pos: Vec2) -> f32 {
+    let contrast = settings.for_mode(STIPPLE_RENDER_MODE)[1].value_f32();
+    image.darkness(rect, pos).powf(contrast)
+}
+
+pub(crate) fn stipple_radius(settings: &Settings, image: &ImageField, rect: Rect, pos: Vec2) -> f32 {
+    let dot_settings = &settings.for_mode(STIPPLE_RENDER_MODE)[0];
+    if dot_settings.bool {
+        dot_settings.value_f32() * (0.25 + stipple_darkness(settings, image, rect, pos))
+    } else {
+        dot_settings.value_f32()
+    }
+}
+
+/// Writes the stipple dots as an SVG document, one circle per point, in world units across `rect`
+pub(crate) fn stipple_svg(settings: &Settings, image: &ImageField, points: &[Point], rect: Rect) -> String {
+    let mut svg = String::new();
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = rect.w(),
+        h = rect.h()
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+
+    for point in points.iter().filter(|p| rect.contains(p.pos)) {
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="black"/>"#,
+            point.pos.x - rect.left(),
+            rect.top() - point.pos.y,
+            stipple_radius(settings, image, rect, point.pos)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub(crate) fn save_stipple_svg(settings: &Settings, image: &ImageField, points: &[Point], rect: Rect) {
+    save_svg("stipple.svg", &stipple_svg(settings, image, points, rect));
+}
@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+use crate::sketch::*;
+use crate::sketch::renderers::flow::STREAMLINES_RENDER_MODE;
+
+/// A traced sample, with the line it belongs to and how far along it, negative behind the seed
+#[derive(Copy, Clone, Debug)]
+struct Sample {
+    pos: Vec2,
+    line: usize,
+    order: i64,
+}
+
+/// Buckets of streamline samples, one bucket per `cell` sized square of the world
+struct SampleGrid {
+    world: Rect,
+    cell: f32,
+    cols: usize,
+    rows: usize,
+    buckets: Vec<Vec<Sample>>,
+}
+
+impl SampleGrid {
+    fn new(world: Rect, cell: f32) -> SampleGrid {
+        let cols = (world.w() / cell).ceil().max(1.0) as usize;
+        let rows = (world.h() / cell).ceil().max(1.0) as usize;
+        SampleGrid {
+            world,
+            cell,
+            cols,
+            rows,
+            buckets: vec![vec![]; cols * rows],
+        }
+    }
+
+    fn cell_of(&self, pos: Vec2) -> (i32, i32) {
+        (((pos.x - self.world.left()) / self.cell).floor() as i32, ((pos.y - self.world.bottom()) / self.cell).floor() as i32)
+    }
+
+    fn bucket(&self, x: i32, y: i32) -> Option<usize> {
+        if x >= 0 && y >= 0 && (x as usize) < self.cols && (y as usize) < self.rows {
+            Some(y as usize * self.cols + x as usize)
+        } else {
+            None
+        }
+    }
+
+    fn insert(&mut self, sample: Sample) {
+        let (x, y) = self.cell_of(sample.pos);
+        if let Some(i) = self.bucket(x, y) {
+            self.buckets[i].push(sample);
+        }
+    }
+
+    /// Takes a line that was too short to keep back out of the grid
+    fn remove_line(&mut self, line: usize, positions: &[Vec2]) {
+        for pos in positions {
+            let (x, y) = self.cell_of(*pos);
+            if let Some(i) = self.bucket(x, y) {
+                self.buckets[i].retain(|s| s.line != line);
+            }
+        }
+    }
+
+    /// Inside the world and no sample closer than `distance`, which must not be more than the cell size.
+    /// Samples of the line being traced are only ignored within `lag` steps of its tip at `tip`.
+    fn is_free(&self, pos: Vec2, distance: f32, tip: Option<Sample>, lag: i64) -> bool {
+        if !self.world.contains(pos) {
+            return false;
+        }
+        let (cx, cy) = self.cell_of(pos);
+        for y in (cy - 1)..=(cy + 1) {
+            for x in (cx - 1)..=(cx + 1) {
+                let i = match self.bucket(x, y) {
+                    Some(i) => i,
+                    None => continue,
+                };
+                let blocked = self.buckets[i].iter().any(|s| {
+                    let behind_tip = tip.map_or(false, |t| t.line == s.line && (t.order - s.order).abs() <= lag);
+                    !behind_tip && s.pos.distance(pos) < distance
+                });
+                if blocked {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// What a set of streamlines was traced through and with
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct TracedWith {
+    noise: NoiseState,
+    flow_field: FlowField,
+    world: Rect,
+    separation: f32,
+    max_samples: usize,
+}
+
+/// The streamlines shown by the streamlines render mode. Laying them out is too slow to do every frame,
+/// so they are only traced again when the noise moves on or the field or settings change.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Streamlines {
+    traced_with: Option<TracedWith>,
+    pub lines: Vec<Vec<Vec2>>,
+}
+
+pub(crate) fn update_streamlines(model: &mut Model) {
+    if model.settings.render_mode != STREAMLINES_RENDER_MODE {
+        model.streamlines = Streamlines::default();
+        return;
+    }
+
+    let settings = model.settings.for_mode(STREAMLINES_RENDER_MODE);
+    let traced_with = Some(TracedWith {
+        noise: model.noise.state(),
+        flow_field: model.settings.flow_field,
+        world: model.world,
+        separation: settings[0].value_f32(),
+        max_samples: settings[1].value_u32() as usize,
+    });
+    if model.streamlines.traced_with == traced_with {
+        return;
+    }
+
+    let (flow_field, world) = (model.settings.flow_field, model.world);
+    let field = |pos: Vec2| model.noise.flow(flow_field, world, pos);
+    let lines = streamlines(field, world, settings[0].value_f32(), settings[1].value_u32() as usize);
+    model.streamlines = Streamlines { traced_with, lines };
+}
+
+/// Evenly spaced streamlines through `field` after Jobard and Lefer.
+/// New lines are seeded `separation` to either side of existing ones, and a line stops
+/// once it comes within half the separation of another line, or of itself further back than a few steps,
+/// so closed orbits end after one loop.
+fn streamlines(field: impl Fn(Vec2) -> Vec2, world: Rect, separation: f32, max_samples: usize) -> Vec<Vec<Vec2>> {
+    let step = separation / 4.0;
+    let test = separation / 2.0;
+    // samples this close along the line are neighbours, not the line running into itself
+    let lag = (separation / step).ceil() as i64;
+    let mut grid = SampleGrid::new(world, separation);
+    let mut lines: Vec<Vec<Vec2>> = vec![];
+    let mut queue = VecDeque::new();
+
+    let direction = |pos: Vec2| {
+        let flow = field(pos);
+        if flow.length() < 1e-6 { None } else { Some(flow.normalize()) }
+    };
+
+    // midpoint method, following the flow forwards or backwards
+    let advance = |pos: Vec2, sign: f32| -> Option<Vec2> {
+        let half = pos + direction(pos)? * sign * step / 2.0;
+        Some(pos + direction(half)? * sign * step)
+    };
+
+    // samples go into the grid as they are traced, so the line and its two halves keep clear of each other
+    let trace = |grid: &mut SampleGrid, line: usize, seed: Vec2| -> Vec<Vec2> {
+        grid.insert(Sample { pos: seed, line, order: 0 });
+        let mut halves = [vec![], vec![]];
+        for (half, sign) in halves.iter_mut().zip([1_i64, -1]) {
+            let mut pos = seed;
+            while half.len() < max_samples / 2 {
+                let tip = Sample { pos, line, order: sign * (half.len() as i64 + 1) };
+                match advance(pos, sign as f32) {
+                    Some(next) if grid.is_free(next, test, Some(tip), lag) => {
+                        grid.insert(Sample { pos: next, ..tip });
+                        half.push(next);
+                        pos = next;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        let [forward, backward] = halves;
+        backward.into_iter().rev().chain(std::iter::once(seed)).chain(forward).collect()
+    };
+
+    // seeds on a coarse lattice cover regions the lines never grow into
+    let lattice = (0..grid.rows).step_by(2).flat_map(|y| (0..grid.cols).step_by(2).map(move |x| (x, y)));
+    for (x, y) in lattice {
+        let seed = world.bottom_left() + Vec2::new(x as f32 + 0.5, y as f32 + 0.5) * separation;
+        queue.push_back(seed);
+
+        while let Some(seed) = queue.pop_front() {
+            if !grid.is_free(seed, separation, None, lag) {
+                continue;
+            }
+            let line = trace(&mut grid, lines.len(), seed);
+            if line.len() < 3 {
+                grid.remove_line(lines.len(), &line);
+                continue;
+            }
+
+            for pair in line.windows(2).step_by(2) {
+                let normal = (pair[1] - pair[0]).perp().normalize_or_zero() * separation;
+                queue.push_back(pair[0] + normal);
+                queue.push_back(pair[0] - normal);
+            }
+            lines.push(line);
+        }
+    }
+
+    lines
+}
@@ -0,0 +1,179 @@
+use nannou::winit::event::MouseScrollDelta;
+use crate::sketch::*;
+
+/// What the mouse buttons do when clicking outside the settings window
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Tool {
+    Off,
+    Paint,
+    Drag,
+    /// Picks the source and target of a shortest path
+    Path,
+}
+
+impl SettingsChoice for Tool {
+    const ALL: &'static [Self] = &[Tool::Off, Tool::Paint, Tool::Drag, Tool::Path];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::Off => "Off",
+            Tool::Paint => "Paint / erase",
+            Tool::Drag => "Drag / pin",
+            Tool::Path => "Path source / target",
+        }
+    }
+}
+
+/// Where painted points get their colour from
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum PaintColour {
+    Picked,
+    Image,
+}
+
+impl SettingsChoice for PaintColour {
+    const ALL: &'static [Self] = &[PaintColour::Picked, PaintColour::Image];
+
+    fn name(&self) -> &'static str {
+        match self {
+            PaintColour::Picked => "Picked colour",
+            PaintColour::Image => "Sample image",
+        }
+    }
+}
+
+/// Mouse state for the tools, set by the press and release events and applied in `update`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Brush {
+    pub painting: bool,
+    pub erasing: bool,
+    pub last_paint: Option<Vec2>,
+    /// id of the point being dragged
+    pub dragging: Option<usize>,
+    /// Last screen position of the cursor while dragging the view
+    pub panning: Option<Vec2>,
+}
+
+pub(crate) fn tool_pressed(app: &App, model: &mut Model, button: MouseButton) {
+    if model.gui.ctx().is_pointer_over_area() {
+        return;
+    }
+    let mouse = model.mouse_world(app);
+    match (model.settings.tool, button) {
+        (Tool::Off, MouseButton::Left) | (_, MouseButton::Middle) => {
+            model.brush.panning = Some(app.mouse.position());
+        }
+        (Tool::Paint, MouseButton::Left) => model.brush.painting = true,
+        (Tool::Paint, MouseButton::Right) => model.brush.erasing = true,
+        (Tool::Drag, MouseButton::Left) => {
+            model.brush.dragging = nearest_point(&model.points, mouse).map(|i| model.points[i].id);
+        }
+        (Tool::Drag, MouseButton::Right) => {
+            if let Some(i) = nearest_point(&model.points, mouse) {
+                model.points[i].pinned = !model.points[i].pinned;
+            }
+        }
+        (Tool::Path, MouseButton::Left) => {
+            model.path_ends.source = nearest_point(&model.points, mouse).map(|i| model.points[i].id);
+        }
+        (Tool::Path, MouseButton::Right) => {
+            model.path_ends.target = nearest_point(&model.points, mouse).map(|i| model.points[i].id);
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn tool_released(model: &mut Model, button: MouseButton) {
+    if button == MouseButton::Left || button == MouseButton::Middle {
+        model.brush.panning = None;
+    }
+
+    match button {
+        MouseButton::Left => {
+            model.brush.painting = false;
+            model.brush.last_paint = None;
+
+            if let Some(id) = model.brush.dragging.take() {
+                let pin = model.settings.pin_dragged;
+                model.points.iter_mut().filter(|p| p.id == id).for_each(|p| p.pinned |= pin);
+            }
+        }
+        MouseButton::Right => model.brush.erasing = false,
+        _ => {}
+    }
+}
+
+/// Pans the view while the cursor drags it, `pos` is in screen space
+pub(crate) fn tool_moved(model: &mut Model, pos: Vec2) {
+    if let Some(last) = model.brush.panning {
+        model.camera.pan(pos - last);
+        model.brush.panning = Some(pos);
+    }
+}
+
+/// Scroll wheel notches, with trackpad pixels scaled to roughly match
+pub(crate) fn scroll_amount(delta: MouseScrollDelta) -> f32 {
+    match delta {
+        MouseScrollDelta::LineDelta(_, y) => y,
+        MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 20.0,
+    }
+}
+
+pub(crate) fn tool_scrolled(model: &mut Model, delta: MouseScrollDelta) {
+    let radius = model.settings.brush_radius.value_f32();
+    model.settings.brush_radius.set_value_f32(radius * 1.1_f32.powf(scroll_amount(delta)));
+}
+
+/// Paint spawns a point under the cursor every time it has moved far enough and removes every point under the brush.
+/// Drag moves the grabbed point, and where it should return to, onto the cursor.
+pub(crate) fn apply_tool(app: &App, model: &mut Model) {
+    let mouse = model.mouse_world(app);
+
+    if let Some(id) = model.brush.dragging {
+        for point in model.points.iter_mut().filter(|p| p.id == id) {
+            point.pos = mouse;
+            point.last_pos = mouse;
+            point.starting_location = mouse;
+            point.moving_vec = Vec2::ZERO;
+        }
+    }
+
+    if model.settings.tool != Tool::Paint {
+        return;
+    }
+
+    if model.brush.painting {
+        let spacing = model.settings.paint_spacing.value_f32();
+        let far_enough = model.brush.last_paint.map_or(true, |last| last.distance(mouse) >= spacing);
+        if far_enough {
+            let colour = match model.settings.paint_source {
+                PaintColour::Picked => {
+                    // the picker edits sRGB, points are coloured in linear light
+                    let [r, g, b] = model.settings.paint_colour;
+                    Srgb::new(r, g, b).into_linear()
+                }
+                PaintColour::Image => model.image.colour(model.world, mouse),
+            };
+            model.points.push(Point::new(mouse, colour));
+            model.brush.last_paint = Some(mouse);
+        }
+    }
+
+    if model.brush.erasing {
+        let radius = model.settings.brush_radius.value_f32();
+        model.points.retain(|p| p.pos.distance(mouse) > radius);
+    }
+}
+
+/// `draw` should be in world space so the brush matches the area it erases
+pub(crate) fn draw_tool(app: &App, draw: &Draw, model: &Model) {
+    if model.settings.tool != Tool::Paint {
+        return;
+    }
+    draw.ellipse()
+        .xy(model.mouse_world(app))
+        .radius(model.settings.brush_radius.value_f32())
+        .no_fill()
+        .stroke(WHITE)
+        .stroke_weight(1.5);
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use nannou::event::{TouchEvent, TouchPhase};
+use crate::sketch::*;
+
+/// Fingers currently on the screen, tracked by touch id
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Touches {
+    /// Screen positions of the active touches
+    pub active: HashMap<u64, Vec2>,
+    /// Normalised force of the active touches, only reported by some platforms
+    pub pressure: HashMap<u64, f32>,
+    /// Force touch on a touchpad, scales the mouse push
+    pub touchpad_pressure: f32,
+}
+
+impl Touches {
+    /// Two fingers navigate the view instead of pushing when gestures are on
+    pub(crate) fn is_gesture(&self, settings: &Settings) -> bool {
+        settings.touch_push.bool && self.active.len() == 2 && settings.touch_gestures
+    }
+
+    /// World position and strength multiplier of every touch acting as a push source
+    pub(crate) fn push_sources(&self, settings: &Settings, camera: &Camera) -> Vec<(Vec2, f32)> {
+        if !settings.touch_push.bool || self.is_gesture(settings) {
+            return vec![];
+        }
+        self.active.iter().map(|(id, pos)| {
+            let pressure = self.pressure.get(id).copied().unwrap_or(1.0);
+            (camera.screen_to_world(*pos), pressure)
+        }).collect()
+    }
+}
+
+pub(crate) fn touch_event(model: &mut Model, touch: TouchEvent) {
+    match touch.phase {
+        TouchPhase::Started => {
+            model.touches.active.insert(touch.id, touch.position);
+        }
+        TouchPhase::Moved => {
+            if model.touches.is_gesture(&model.settings) {
+                pinch(model, touch.id, touch.position);
+            }
+            model.touches.active.insert(touch.id, touch.position);
+        }
+        TouchPhase::Ended | TouchPhase::Cancelled => {
+            model.touches.active.remove(&touch.id);
+            model.touches.pressure.remove(&touch.id);
+        }
+    }
+}
+
+/// Records the force of a touch, which nannou's `TouchEvent` leaves out
+pub(crate) fn raw_touch_event(model: &mut Model, touch: &nannou::winit::event::Touch) {
+    if let Some(force) = touch.force {
+        model.touches.pressure.insert(touch.id, force.normalized() as f32);
+    }
+}
+
+/// Pans by how far the midpoint of the two fingers moved and zooms by how much their distance changed
+fn pinch(model: &mut Model, id: u64, position: Vec2) {
+    let other = match model.touches.active.iter().find(|(other_id, _)| **other_id != id) {
+        Some((_, pos)) => *pos,
+        None => return,
+    };
+    let previous = match model.touches.active.get(&id) {
+        Some(pos) => *pos,
+        None => return,
+    };
+
+    let mid_before = (previous + other) / 2.0;
+    let mid_after = (position + other) / 2.0;
+    model.camera.pan(mid_after - mid_before);
+
+    let distance_before = previous.distance(other);
+    if distance_before > 0.0 {
+        model.camera.zoom_around(mid_after, position.distance(other) / distance_before);
+    }
+}
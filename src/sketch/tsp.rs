@@ -0,0 +1,433 @@
+use std::fmt::Write;
+use crate::sketch::*;
+use crate::sketch::cell_style::chaikin;
+use crate::sketch::delaunay::{delaunay_edges, delaunay_neighbours};
+use crate::sketch::kruskals::UnionFind;
+
+pub(crate) const TOUR_RENDER_MODE: u8 = 13;
+
+/// How the first tour is put together before it gets improved
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum TourConstruction {
+    /// Always go to the closest point not yet visited
+    NearestNeighbour,
+    /// Take the shortest Delaunay edges that keep every point on a single path, then close it
+    Greedy,
+}
+
+impl SettingsChoice for TourConstruction {
+    const ALL: &'static [Self] = &[TourConstruction::NearestNeighbour, TourConstruction::Greedy];
+
+    fn name(&self) -> &'static str {
+        match self {
+            TourConstruction::NearestNeighbour => "Nearest neighbour",
+            TourConstruction::Greedy => "Greedy edge",
+        }
+    }
+}
+
+/// A closed tour through every point, improved a little every frame while the points move
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tour {
+    /// Indices into the points in visiting order
+    pub order: Vec<usize>,
+    /// Where each point is in `order`
+    position: Vec<usize>,
+    /// Ids of the points the tour was built for, it is built again when these change
+    ids: Vec<usize>,
+    construction: Option<TourConstruction>,
+    /// The point improvement carries on from next frame
+    cursor: usize,
+}
+
+impl Tour {
+    fn new(order: Vec<usize>, ids: Vec<usize>, construction: TourConstruction) -> Tour {
+        let mut tour = Tour { order, position: vec![], ids, construction: Some(construction), cursor: 0 };
+        tour.update_positions();
+        tour
+    }
+
+    fn update_positions(&mut self) {
+        self.position = vec![0; self.order.len()];
+        for (i, &city) in self.order.iter().enumerate() {
+            self.position[city] = i;
+        }
+    }
+
+    fn next(&self, city: usize) -> usize {
+        self.order[(self.position[city] + 1) % self.order.len()]
+    }
+
+    fn previous(&self, city: usize) -> usize {
+        let n = self.order.len();
+        self.order[(self.position[city] + n - 1) % n]
+    }
+
+    /// Reverses the cyclic run of positions `from..=to`, or the rest of the tour if that is shorter, which is the same tour
+    fn reverse(&mut self, from: usize, to: usize) {
+        let n = self.order.len();
+        let mut len = (to + n - from) % n + 1;
+        let (mut from, mut to) = (from, to);
+        if len * 2 > n {
+            len = n - len;
+            (from, to) = ((to + 1) % n, (from + n - 1) % n);
+        }
+        for _ in 0..len / 2 {
+            self.order.swap(from, to);
+            self.position[self.order[from]] = from;
+            self.position[self.order[to]] = to;
+            from = (from + 1) % n;
+            to = (to + n - 1) % n;
+        }
+    }
+
+    /// 2-opt: swaps the edge leaving `a` and the edge leaving one of its neighbours for the two edges that join
+    /// them up the other way, if that is shorter. Only neighbours closer than the edge being removed can help.
+    fn two_opt(&mut self, points: &[Vec2], neighbours: &[Vec<usize>], a: usize) -> bool {
+        let distance = |x: usize, y: usize| points[x].distance(points[y]);
+        let a_next = self.next(a);
+        let removed = distance(a, a_next);
+
+        for &c in neighbours[a].iter() {
+            let added = distance(a, c);
+            if added >= removed {
+                continue;
+            }
+            let c_next = self.next(c);
+            if c == a_next || c_next == a {
+                continue;
+            }
+
+            if added + distance(a_next, c_next) < removed + distance(c, c_next) - 1e-4 {
+                self.reverse((self.position[a] + 1) % self.order.len(), self.position[c]);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Or-opt: moves the run of up to three points starting at `a` between two neighbouring points elsewhere, either way round
+    fn or_opt(&mut self, points: &[Vec2], neighbours: &[Vec<usize>], a: usize) -> bool {
+        let distance = |x: usize, y: usize| points[x].distance(points[y]);
+        let n = self.order.len();
+        if n < 8 {
+            return false;
+        }
+
+        for run_len in 1..=3 {
+            let start = self.position[a];
+            let run = (0..run_len).map(|k| self.order[(start + k) % n]).collect::<Vec<usize>>();
+            let (first, last) = (run[0], run[run_len - 1]);
+            let (before, after) = (self.previous(first), self.next(last));
+            let saved = distance(before, first) + distance(last, after) - distance(before, after);
+
+            for &c in neighbours[first].iter().chain(neighbours[last].iter()) {
+                let c_next = self.next(c);
+                if run.contains(&c) || run.contains(&c_next) {
+                    continue;
+                }
+
+                let forward = distance(c, first) + distance(last, c_next) - distance(c, c_next);
+                let backward = distance(c, last) + distance(first, c_next) - distance(c, c_next);
+                if forward.min(backward) < saved - 1e-4 {
+                    let mut run = run.clone();
+                    if backward < forward {
+                        run.reverse();
+                    }
+                    let mut order = self.order.iter().copied().filter(|x| !run.contains(x)).collect::<Vec<usize>>();
+                    let at = order.iter().position(|&x| x == c).unwrap() + 1;
+                    order.splice(at..at, run);
+                    self.order = order;
+                    self.update_positions();
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Runs 2-opt and Or-opt moves round the tour until `budget_ms` is used up or a whole lap finds nothing
+    fn improve(&mut self, points: &[Vec2], neighbours: &[Vec<usize>], budget_ms: f64) {
+        let n = self.order.len();
+        if n < 5 {
+            return;
+        }
+
+        let start = now_ms();
+        let mut since_improvement = 0;
+        while since_improvement < n {
+            let a = self.order[self.cursor % n];
+            if self.two_opt(points, neighbours, a) || self.or_opt(points, neighbours, a) {
+                since_improvement = 0;
+            } else {
+                since_improvement += 1;
+            }
+            self.cursor = (self.cursor + 1) % n;
+
+            // checking the clock isn't free, so only every few moves
+            if self.cursor % 16 == 0 && now_ms() - start > budget_ms {
+                break;
+            }
+        }
+    }
+}
+
+fn nearest_neighbour_tour(points: &[Vec2]) -> Vec<usize> {
+    let n = points.len();
+    if n == 0 {
+        return vec![];
+    }
+
+    let mut visited = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let mut current = 0;
+    visited[current] = true;
+    order.push(current);
+
+    for _ in 1..n {
+        let from = points[current];
+        current = (0..n)
+            .filter(|&i| !visited[i])
+            .min_by(|&a, &b| from.distance_squared(points[a]).partial_cmp(&from.distance_squared(points[b])).unwrap_or(Ordering::Equal))
+            .unwrap();
+        visited[current] = true;
+        order.push(current);
+    }
+    order
+}
+
+fn greedy_tour(points: &[Vec2], candidates: Vec<(usize, usize)>) -> Vec<usize> {
+    let n = points.len();
+    if n < 3 {
+        return (0..n).collect();
+    }
+    let distance = |(a, b): (usize, usize)| points[a].distance(points[b]);
+
+    let mut links: Vec<Vec<usize>> = vec![vec![]; n];
+    let mut fragment = UnionFind::new(n);
+    let mut fragments = n;
+    let link = |a: usize, b: usize, links: &mut Vec<Vec<usize>>, fragment: &mut UnionFind| {
+        links[a].push(b);
+        links[b].push(a);
+        fragment.union(a, b);
+    };
+
+    let mut candidates = candidates;
+    candidates.sort_by(|x, y| distance(*x).partial_cmp(&distance(*y)).unwrap_or(Ordering::Equal));
+    for (a, b) in candidates {
+        if links[a].len() < 2 && links[b].len() < 2 && fragment.find(a) != fragment.find(b) {
+            link(a, b, &mut links, &mut fragment);
+            fragments -= 1;
+        }
+    }
+
+    // the candidates rarely make one path, so the loose ends get joined to the nearest end of another fragment
+    while fragments > 1 {
+        let ends = (0..n).filter(|&i| links[i].len() < 2).collect::<Vec<usize>>();
+        let a = ends[0];
+        let root_a = fragment.find(a);
+        let b = ends.iter()
+            .copied()
+            .filter(|&b| fragment.find(b) != root_a)
+            .min_by(|&x, &y| distance((a, x)).partial_cmp(&distance((a, y))).unwrap_or(Ordering::Equal))
+            .unwrap();
+        link(a, b, &mut links, &mut fragment);
+        fragments -= 1;
+    }
+
+    // close the path into a loop
+    let ends = (0..n).filter(|&i| links[i].len() < 2).collect::<Vec<usize>>();
+    if let [a, b] = ends[..] {
+        links[a].push(b);
+        links[b].push(a);
+    }
+
+    let mut order = vec![0];
+    let (mut previous, mut current) = (usize::MAX, 0);
+    while order.len() < n {
+        let next = if links[current][0] != previous { links[current][0] } else { links[current][1] };
+        previous = current;
+        current = next;
+        order.push(current);
+    }
+    order
+}
+
+/// Builds the tour again if the points changed, then spends the frame's budget improving it
+pub(crate) fn update_tour(model: &mut Model) {
+    if model.settings.render_mode != TOUR_RENDER_MODE {
+        return;
+    }
+
+    let settings = model.settings.for_mode(TOUR_RENDER_MODE);
+    let improving = settings[0].bool;
+    let budget_ms = settings[0].value_f32() as f64;
+    let construction = model.settings.tour_construction;
+
+    let ids = model.points.iter().map(|p| p.id).collect::<Vec<usize>>();
+    let points = model.points.iter().map(|p| p.pos).collect::<Vec<Vec2>>();
+    if ids != model.tour.ids || model.tour.construction != Some(construction) {
+        let order = match construction {
+            TourConstruction::NearestNeighbour => nearest_neighbour_tour(&points),
+            TourConstruction::Greedy => greedy_tour(&points, delaunay_edges(model)),
+        };
+        model.tour = Tour::new(order, ids, construction);
+    }
+
+    if improving {
+        let neighbours = delaunay_neighbours(model);
+        model.tour.improve(&points, &neighbours, budget_ms);
+    }
+}
+
+/// The tour as a closed outline, smoothed if that is switched on, with the first point repeated at the end
+pub(crate) fn tour_outline(settings: &Settings, tour: &Tour, points: &[Point]) -> Vec<Vec2> {
+    let mut outline = tour.order.iter()
+        .filter_map(|&i| points.get(i))
+        .map(|p| p.pos)
+        .collect::<Vec<Vec2>>();
+
+    let smoothing = &settings.for_mode(TOUR_RENDER_MODE)[2];
+    if smoothing.bool {
+        outline = chaikin(&outline, smoothing.value_u32() as usize);
+    }
+    outline.extend(outline.first().copied());
+    outline
+}
+
+/// The tour as one path in an SVG document in world units across `rect`, ready for a pen plotter
+pub(crate) fn tour_svg(settings: &Settings, tour: &Tour, points: &[Point], rect: Rect) -> String {
+    let mut svg = String::new();
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = rect.w(),
+        h = rect.h()
+    );
+
+    let mut path = String::new();
+    for (i, pos) in tour_outline(settings, tour, points).iter().enumerate() {
+        let _ = write!(path, "{}{:.2} {:.2} ", if i == 0 { "M" } else { "L" }, pos.x - rect.left(), rect.top() - pos.y);
+    }
+    let _ = writeln!(svg, r#"<path d="{}Z" fill="none" stroke="black" stroke-width="1"/>"#, path);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scattered points from a fixed seed, so failures can be reproduced
+    fn scattered(n: usize) -> Vec<Vec2> {
+        let mut state: u32 = 12345;
+        let mut next = move || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32
+        };
+        (0..n).map(|_| Vec2::new(next() * 500.0, next() * 300.0)).collect()
+    }
+
+    /// The closest few points to each, standing in for the Delaunay neighbours
+    fn nearest(points: &[Vec2], k: usize) -> Vec<Vec<usize>> {
+        (0..points.len()).map(|i| {
+            let mut others = (0..points.len()).filter(|&j| j != i).collect::<Vec<usize>>();
+            others.sort_by(|&a, &b| points[i].distance(points[a]).total_cmp(&points[i].distance(points[b])));
+            others.truncate(k);
+            others
+        }).collect()
+    }
+
+    fn length(points: &[Vec2], order: &[usize]) -> f32 {
+        (0..order.len()).map(|i| points[order[i]].distance(points[order[(i + 1) % order.len()]])).sum()
+    }
+
+    fn assert_permutation(tour: &Tour, n: usize) {
+        let mut sorted = tour.order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..n).collect::<Vec<usize>>());
+        for (i, &city) in tour.order.iter().enumerate() {
+            assert_eq!(tour.position[city], i);
+        }
+    }
+
+    /// The tour's edges without direction, which is what stays the same however a cyclic tour is written down
+    fn edges(order: &[usize]) -> Vec<(usize, usize)> {
+        let mut edges = (0..order.len()).map(|i| {
+            let (a, b) = (order[i], order[(i + 1) % order.len()]);
+            (a.min(b), a.max(b))
+        }).collect::<Vec<(usize, usize)>>();
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn reverse_matches_reversing_the_run() {
+        for n in [5, 6, 7, 8] {
+            for from in 0..n {
+                for to in 0..n {
+                    let mut tour = Tour::new((0..n).collect(), vec![], TourConstruction::NearestNeighbour);
+                    tour.reverse(from, to);
+                    assert_permutation(&tour, n);
+
+                    // the run reversed in place the long way, wrapping past the end
+                    let mut expected = (0..n).collect::<Vec<usize>>();
+                    let len = (to + n - from) % n + 1;
+                    let run = (0..len).map(|k| expected[(from + k) % n]).collect::<Vec<usize>>();
+                    for (k, city) in run.into_iter().rev().enumerate() {
+                        expected[(from + k) % n] = city;
+                    }
+                    assert_eq!(edges(&tour.order), edges(&expected), "n {} from {} to {}", n, from, to);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn moves_keep_a_permutation_and_never_lengthen_the_tour() {
+        let points = scattered(60);
+        let neighbours = nearest(&points, 8);
+        let mut tour = Tour::new((0..points.len()).collect(), vec![], TourConstruction::NearestNeighbour);
+
+        let mut best = length(&points, &tour.order);
+        let mut moves = [0; 2];
+        for round in 0..6 {
+            for a in 0..points.len() {
+                let moved = if round % 2 == 0 {
+                    tour.two_opt(&points, &neighbours, a)
+                } else {
+                    tour.or_opt(&points, &neighbours, a)
+                };
+                assert_permutation(&tour, points.len());
+                let now = length(&points, &tour.order);
+                assert!(now <= best + 1e-3, "{} after {} at {}", now, best, a);
+                if moved {
+                    assert!(now < best);
+                    moves[round % 2] += 1;
+                }
+                best = now;
+            }
+        }
+        // a tour in index order is a long way from good, so both kinds of move must have found something
+        assert!(moves[0] > 0 && moves[1] > 0, "{:?}", moves);
+    }
+
+    #[test]
+    fn constructions_visit_every_point_once() {
+        for n in [3, 4, 10, 60] {
+            let points = scattered(n);
+            let candidates = nearest(&points, 4).into_iter()
+                .enumerate()
+                .flat_map(|(i, near)| near.into_iter().map(move |j| (i.min(j), i.max(j))))
+                .collect::<Vec<(usize, usize)>>();
+
+            // no candidates at all leaves every fragment joined up at its loose ends
+            for order in [nearest_neighbour_tour(&points), greedy_tour(&points, candidates), greedy_tour(&points, vec![])] {
+                let tour = Tour::new(order, vec![], TourConstruction::Greedy);
+                assert_permutation(&tour, n);
+            }
+        }
+    }
+}
@@ -12,55 +12,20 @@ pub (crate) fn draw_double_circle(draw: &Draw, location: Vec2, colour: LinSrgb<f
         .x_y(location.x, location.y);
 }
 
-pub (crate) fn colour_from_zero_one(x: f32) -> LinSrgb<f32> {
-    let okhsv: palette::Okhsv = palette::Okhsv::new(x * 360.0, 0.8, 1.0).clamp();
-    let linsrgb = palette::LinSrgb::from_color_unclamped(okhsv);
-    lin_srgb(linsrgb.red, linsrgb.green, linsrgb.blue)
-
-    // let x = hsv(x, 1.0, 1.0).into_lin_srgba();
-    // lin_srgb(x.red, x.green, x.blue)
-}
-
-pub (crate) fn okhsv_to_linsrgb(h: f32, s: f32, v: f32) -> LinSrgb<f32> {
-    let okhsv: palette::Okhsv = palette::Okhsv::new(h * 360.0, s, v).clamp();
-    let linsrgb = palette::LinSrgb::from_color_unclamped(okhsv);
-    lin_srgb(linsrgb.red, linsrgb.green, linsrgb.blue)
-}
-
-pub (crate) fn average_lin_srgb(v: &Vec<LinSrgb<f32>>) -> LinSrgb<f32> {
-    let colour = v.iter()
-        .fold((0.0_f32, 0.0_f32, 0.0_f32), |col, x| {
-            (col.0 + x.red, col.1 + x.green, col.2 + x.blue)
-        });
-    let black = lin_srgb(0.0, 0.0, 0.0);
-    let len = v.iter().filter(|&&x| { !(x == black) }).count() as f32;
-    lin_srgb(colour.0 / len, colour.1 / len, colour.2 / len)
-}
-
 pub (crate) fn transmute_f32_to_u32(x: f32) -> u32 {
     unsafe { std::mem::transmute::<f32, u32>(x) }
 }
 
-pub (crate) fn tileable_perlin(settings: &Settings, app: &App, pos: Vec2) -> f32 {
-    let seed = settings.perlin_seed.value_u32();
-    let size = app.window_rect().wh();
-    let scale = settings.settings_per_render_mode[0].as_ref().unwrap()[0].value_f32();
+/// Push away from `source` that falls off exponentially with distance
+pub(crate) fn radial_push(pos: Vec2, source: Vec2) -> Vec2 {
+    let vec = pos - source;
+    let dir = vec.normalize_or_zero();
+    let distance = vec.length();
 
-    let x_prop = pos.x / size.x + 0.5;
-    let y_prop = pos.y / size.y + 0.5;
+    // let new_length = 1.0 / distance.powf(0.7);
+    let new_length = E.powf(-1.0 / 100.0 * distance);
 
-    let angle_x = x_prop * TAU;
-    let angle_y = y_prop * TAU;
-
-    let a = cos(angle_x) * scale;
-    let b = sin(angle_x) * scale;
-    let c = cos(angle_y) * scale;
-    let d = sin(angle_y) * scale;
-
-    let noise = OpenSimplex::new(seed);
-
-    let v = (noise.get([a as f64, b as f64, c as f64, d as f64])) as f32 + 0.5;
-    v
+    dir * new_length
 }
 
 pub(crate) fn draw_all_points(draw: &Draw, model: &Model) {
@@ -74,4 +39,59 @@ pub(crate) fn draw_point(draw: &Draw, point: &Point, model: &Model) {
     if model.settings.show_points.bool || mode == 0 || mode == 6 || mode == 9 {
         draw_double_circle(draw, point.pos, point.colour, model.settings.show_points.value_f32());
     }
-}
\ No newline at end of file
+
+    // pinned points are always shown so the anchors of a layout can be found again
+    if point.pinned {
+        draw.rect()
+            .xy(point.pos)
+            .w_h(10.0, 10.0)
+            .rotate(FRAC_PI_4)
+            .no_fill()
+            .stroke(WHITE)
+            .stroke_weight(2.0);
+    }
+}
+
+/// Reports something to whoever is running the sketch, on stderr or in the browser console on the web
+pub(crate) fn log_message(message: &str) {
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", message);
+
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::log_1(&JsValue::from_str(message));
+}
+
+/// Writes an SVG document into the working directory, or logs it to the console on the web
+pub(crate) fn save_svg(file_name: &str, svg: &str) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        match std::fs::write(file_name, svg) {
+            Ok(_) => log_message(&format!("Saved {}", file_name)),
+            Err(e) => log_message(&format!("Could not save {}: {}", file_name, e)),
+        }
+    }
+
+    // there is no file system on the web, so hand the document to the console instead
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = file_name;
+        log_message(svg);
+    }
+}
+
+/// Milliseconds since some fixed point, for timing work within a frame. `std::time::Instant` panics on the web.
+pub(crate) fn now_ms() -> f64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0.0, |x| x.as_secs_f64() * 1000.0)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::window()
+            .and_then(|x| x.performance())
+            .map_or(0.0, |x| x.now())
+    }
+}